@@ -1,7 +1,9 @@
+mod quantize;
+
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use rayon::prelude::*;
 use std::{
-    fs,
+    env, fs,
     path::{Path, PathBuf},
     process::{self, Command},
     sync::mpsc::{self, RecvTimeoutError},
@@ -11,49 +13,112 @@ use std::{
 const MANIFEST: &str = "oxocarbon.toml";
 const DEBOUNCE: Duration = Duration::from_millis(150);
 const THEMES_DIR: &str = "themes";
+const PACKAGE_JSON: &str = "package.json";
 
 struct ThemeSpec {
     name: &'static str,
+    label: &'static str,
+    ui_theme: &'static str,
     flags: &'static [&'static str],
 }
 
 const THEMES: &[ThemeSpec] = &[
     ThemeSpec {
         name: "oxocarbon-color-theme.json",
+        label: "oxocarbon",
+        ui_theme: "vs-dark",
         flags: &[],
     },
     ThemeSpec {
         name: "oxocarbon-oled-color-theme.json",
+        label: "oxocarbon OLED",
+        ui_theme: "vs-dark",
         flags: &["--oled"],
     },
     ThemeSpec {
         name: "oxocarbon-compat-color-theme.json",
+        label: "oxocarbon (compatibility)",
+        ui_theme: "vs-dark",
         flags: &["--compat"],
     },
     ThemeSpec {
         name: "oxocarbon-oled-compat-color-theme.json",
+        label: "oxocarbon OLED (compatibility)",
+        ui_theme: "vs-dark",
         flags: &["--oled", "--compat"],
     },
     ThemeSpec {
         name: "oxocarbon-mono-color-theme.json",
+        label: "oxocarbon monochrom",
+        ui_theme: "vs-dark",
         flags: &["--monochrome"],
     },
     ThemeSpec {
         name: "oxocarbon-oled-mono-color-theme.json",
+        label: "oxocarbon OLED monochrom",
+        ui_theme: "vs-dark",
         flags: &["--oled", "--monochrome"],
     },
     ThemeSpec {
         name: "oxocarbon-mono-compat-color-theme.json",
+        label: "oxocarbon monochrom (compatibility)",
+        ui_theme: "vs-dark",
         flags: &["--monochrome", "--compat"],
     },
     ThemeSpec {
         name: "oxocarbon-oled-mono-compat-color-theme.json",
+        label: "oxocarbon OLED monochrom (compatibility)",
+        ui_theme: "vs-dark",
         flags: &["--oled", "--monochrome", "--compat"],
     },
     ThemeSpec {
         name: "PRINT.json",
+        label: "oxocarbon PRINT",
+        ui_theme: "vs-light",
         flags: &["--monochrome", "--oled", "--print"],
     },
+    ThemeSpec {
+        name: "oxocarbon-light-color-theme.json",
+        label: "oxocarbon Light",
+        ui_theme: "vs",
+        flags: &["--light"],
+    },
+    ThemeSpec {
+        name: "oxocarbon-light-compat-color-theme.json",
+        label: "oxocarbon Light (compatibility)",
+        ui_theme: "vs",
+        flags: &["--light", "--compat"],
+    },
+    ThemeSpec {
+        name: "oxocarbon-light-mono-color-theme.json",
+        label: "oxocarbon Light monochrom",
+        ui_theme: "vs",
+        flags: &["--light", "--monochrome"],
+    },
+    ThemeSpec {
+        name: "oxocarbon-light-mono-compat-color-theme.json",
+        label: "oxocarbon Light monochrom (compatibility)",
+        ui_theme: "vs",
+        flags: &["--light", "--monochrome", "--compat"],
+    },
+    ThemeSpec {
+        name: "oxocarbon-hc-color-theme.json",
+        label: "oxocarbon High Contrast",
+        ui_theme: "hc-black",
+        flags: &["--hc"],
+    },
+    ThemeSpec {
+        name: "oxocarbon-hc-light-color-theme.json",
+        label: "oxocarbon High Contrast Light",
+        ui_theme: "hc-light",
+        flags: &["--light", "--hc"],
+    },
+    ThemeSpec {
+        name: "oxocarbon-focus-color-theme.json",
+        label: "oxocarbon Focus",
+        ui_theme: "vs-dark",
+        flags: &["--focus"],
+    },
 ];
 
 fn main() {
@@ -65,13 +130,20 @@ fn main() {
 
 fn run() -> Result<(), String> {
     let manifest = manifest_path()?;
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.first().is_some_and(|arg| arg == "quantize") {
+        return report_quantization(&manifest, &args[1..]);
+    }
+    if args.iter().any(|arg| arg == "--once") {
+        return rebuild(&manifest);
+    }
 
     let (tx, rx) = mpsc::channel();
     let mut watcher = RecommendedWatcher::new(tx, Config::default())
         .map_err(|e| format!("Failed to init watcher: {e}"))?;
-    watcher
-        .watch(&manifest, RecursiveMode::NonRecursive)
-        .map_err(|e| format!("Failed to watch {}: {e}", manifest.display()))?;
+    let mut watched = Vec::new();
+    sync_watched(&mut watcher, &mut watched, &manifest)?;
 
     println!("Watching {}", manifest.display());
     rebuild(&manifest)?;
@@ -86,7 +158,10 @@ fn run() -> Result<(), String> {
         }
 
         if pending.is_some_and(|ts| ts.elapsed() >= DEBOUNCE) {
-            if let Err(err) = rebuild(&manifest) {
+            // includes may have changed, so the watch list follows the manifest
+            if let Err(err) = sync_watched(&mut watcher, &mut watched, &manifest)
+                .and_then(|()| rebuild(&manifest))
+            {
                 eprintln!("{err}");
             }
             pending = None;
@@ -96,6 +171,48 @@ fn run() -> Result<(), String> {
     Ok(())
 }
 
+// watches the manifest and every file it includes, dropping files no longer included
+fn sync_watched(
+    watcher: &mut RecommendedWatcher,
+    watched: &mut Vec<PathBuf>,
+    manifest: &Path,
+) -> Result<(), String> {
+    let mut files = Vec::new();
+    manifest_files(manifest, &mut files)?;
+
+    for path in watched.iter().filter(|p| !files.contains(p)) {
+        let _ = watcher.unwatch(path);
+    }
+    for path in files.iter().filter(|p| !watched.contains(p)) {
+        watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {e}", path.display()))?;
+    }
+    *watched = files;
+    Ok(())
+}
+
+// the manifest followed by its `include` list, depth first
+fn manifest_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let path =
+        fs::canonicalize(path).map_err(|e| format!("Failed to resolve {}: {e}", path.display()))?;
+    if files.contains(&path) {
+        return Ok(());
+    }
+    let src =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let table: toml::Table =
+        toml::from_str(&src).map_err(|e| format!("TOML parse error ({}): {e}", path.display()))?;
+    files.push(path.clone());
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let includes = table.get("include").and_then(|v| v.as_array());
+    for include in includes.into_iter().flatten().filter_map(|v| v.as_str()) {
+        manifest_files(&dir.join(include), files)?;
+    }
+    Ok(())
+}
+
 fn rebuild(manifest: &Path) -> Result<(), String> {
     println!("Compiling...");
     let start = Instant::now();
@@ -129,10 +246,102 @@ fn rebuild(manifest: &Path) -> Result<(), String> {
         Ok(())
     })?;
 
+    sync_package_json(&root)?;
+
     println!("Done in {:.2?}", start.elapsed());
     Ok(())
 }
 
+// keep the extension's contributed themes in lockstep with THEMES, splicing the
+// array in place so the rest of package.json keeps its hand formatting
+fn sync_package_json(root: &Path) -> Result<(), String> {
+    let path = root.join(PACKAGE_JSON);
+    let src =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+
+    let key = src
+        .find("\"themes\"")
+        .ok_or_else(|| format!("{}: missing contributes.themes", path.display()))?;
+    let open = key + src[key..].find('[').ok_or("themes must be an array")?;
+    let close = open + matching_bracket(&src[open..]).ok_or("unterminated themes array")?;
+    let line_start = src[..key].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &src[line_start..key];
+
+    let quote = |s: &str| serde_json::to_string(s).expect("string serializes");
+    let entries: Vec<String> = THEMES
+        .iter()
+        .map(|spec| {
+            format!(
+                "{indent}  {{\n\
+                 {indent}    \"label\": {},\n\
+                 {indent}    \"uiTheme\": {},\n\
+                 {indent}    \"path\": {}\n\
+                 {indent}  }}",
+                quote(spec.label),
+                quote(spec.ui_theme),
+                quote(&format!("./{THEMES_DIR}/{}", spec.name)),
+            )
+        })
+        .collect();
+    let out = format!(
+        "{}[\n{}\n{indent}]{}",
+        &src[..open],
+        entries.join(",\n"),
+        &src[close + 1..]
+    );
+
+    serde_json::from_str::<serde_json::Value>(&out)
+        .map_err(|e| format!("Generated invalid {}: {e}", path.display()))?;
+    if out != src {
+        fs::write(&path, out).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn matching_bracket(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, b) in s.bytes().enumerate() {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' if in_str => escaped = true,
+            b'"' => in_str = !in_str,
+            b'[' if !in_str => depth += 1,
+            b']' if !in_str => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// how each theme color survives xterm-256 and ansi-16 terminals, every built theme unless
+// files are given
+fn report_quantization(manifest: &Path, files: &[String]) -> Result<(), String> {
+    let paths: Vec<PathBuf> = if files.is_empty() {
+        let themes_dir = manifest
+            .parent()
+            .ok_or("Manifest missing parent directory")?
+            .join(THEMES_DIR);
+        THEMES
+            .iter()
+            .map(|spec| themes_dir.join(spec.name))
+            .collect()
+    } else {
+        files.iter().map(PathBuf::from).collect()
+    };
+
+    for path in paths {
+        println!("{}", quantize::report(&path)?);
+    }
+    Ok(())
+}
+
 fn is_relevant(event: &Event) -> bool {
     matches!(
         event.kind,
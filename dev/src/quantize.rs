@@ -0,0 +1,125 @@
+use oxocarbon_utils::{
+    composite_over, delta_e, nearest_ansi16, nearest_xterm256, parse_hex_rgba_u8 as parse_hex,
+    xterm256_rgb,
+};
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::Path};
+
+const ANSI_KEYS: [&str; 16] = [
+    "terminal.ansiBlack",
+    "terminal.ansiRed",
+    "terminal.ansiGreen",
+    "terminal.ansiYellow",
+    "terminal.ansiBlue",
+    "terminal.ansiMagenta",
+    "terminal.ansiCyan",
+    "terminal.ansiWhite",
+    "terminal.ansiBrightBlack",
+    "terminal.ansiBrightRed",
+    "terminal.ansiBrightGreen",
+    "terminal.ansiBrightYellow",
+    "terminal.ansiBrightBlue",
+    "terminal.ansiBrightMagenta",
+    "terminal.ansiBrightCyan",
+    "terminal.ansiBrightWhite",
+];
+
+/// every distinct color of a theme with its nearest xterm-256 index and, if the theme sets
+/// all 16 `terminal.ansi*` colors, its nearest ansi color. worst 256 color match first
+pub(crate) fn report(path: &Path) -> Result<String, String> {
+    let src =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let theme: Value = serde_json::from_str(&src)
+        .map_err(|e| format!("JSON parse error ({}): {e}", path.display()))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| format!("{}: missing colors", path.display()))?;
+    let background = colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex)
+        .map(|(rgb, _)| rgb)
+        .ok_or_else(|| format!("{}: invalid editor.background", path.display()))?;
+    // terminals have no alpha, so translucent colors are measured as they'd be drawn
+    let flatten =
+        |hex: &str| parse_hex(hex).map(|(rgb, alpha)| composite_over(rgb, alpha, background));
+
+    // color -> the keys and scopes using it
+    let mut uses: BTreeMap<[u8; 3], Vec<String>> = BTreeMap::new();
+    for (key, value) in colors {
+        if let Some(rgb) = value.as_str().and_then(flatten) {
+            uses.entry(rgb).or_default().push(key.clone());
+        }
+    }
+    for item in theme["tokenColors"].as_array().into_iter().flatten() {
+        let scope = match &item["scope"] {
+            Value::String(s) => s.clone(),
+            Value::Array(arr) => arr
+                .first()
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            _ => continue,
+        };
+        for setting in ["foreground", "background"] {
+            if let Some(rgb) = item["settings"][setting].as_str().and_then(flatten) {
+                uses.entry(rgb).or_default().push(scope.clone());
+            }
+        }
+    }
+
+    let ansi: Option<[[u8; 3]; 16]> = ANSI_KEYS
+        .iter()
+        .map(|key| colors.get(*key).and_then(|v| v.as_str()).and_then(flatten))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|v| v.try_into().ok());
+
+    let mut rows: Vec<_> = uses
+        .into_iter()
+        .map(|(rgb, used_by)| {
+            let index = nearest_xterm256(rgb);
+            let error = delta_e(rgb, xterm256_rgb(index));
+            (rgb, index, error, used_by)
+        })
+        .collect();
+    rows.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let hex = |[r, g, b]: [u8; 3]| format!("#{r:02x}{g:02x}{b:02x}");
+    let mut out = format!(
+        "{}\n  {:<9}{:<16}{:<9}{:<16}{:<9}used by\n",
+        path.display(),
+        "color",
+        "xterm-256",
+        "ΔE",
+        "ansi-16",
+        "ΔE"
+    );
+    for (rgb, index, error, used_by) in rows {
+        let _ = write!(
+            out,
+            "  {:<9}{:<16}{error:<9.4}",
+            hex(rgb),
+            format!("{index} {}", hex(xterm256_rgb(index)))
+        );
+        match &ansi {
+            Some(palette) => {
+                let ansi_index = nearest_ansi16(rgb, palette);
+                let ansi_rgb = palette[usize::from(ansi_index)];
+                let _ = write!(
+                    out,
+                    "{:<16}{:<9.4}",
+                    format!("{ansi_index} {}", hex(ansi_rgb)),
+                    delta_e(rgb, ansi_rgb)
+                );
+            }
+            None => {
+                let _ = write!(out, "{:<16}{:<9}", "-", "-");
+            }
+        }
+        let _ = match used_by.len() {
+            1 => writeln!(out, "{}", used_by[0]),
+            n => writeln!(out, "{} (+{})", used_by[0], n - 1),
+        };
+    }
+    Ok(out)
+}
@@ -0,0 +1,608 @@
+use oxocarbon_utils::{
+    composite_over, format_hex_color, luminance_from_u8, oklch_to_rgb,
+    parse_hex_rgba_u8 as parse_hex_color, rgb_to_oklch,
+};
+use serde_json::Value;
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process,
+};
+
+// below this oklch chroma a candidate doesn't count as an accent
+const ACCENT_CHROMA: f32 = 0.03;
+
+/// where a slot's color comes from in a compiled theme
+enum Source {
+    /// a key of `colors`, translucent values flattened onto base00
+    Color(&'static str),
+    /// the foreground textmate resolves for this scope
+    Scope(&'static str),
+    /// base00 moved this much oklch lightness toward the foreground, for slots a theme has
+    /// no key for. export only
+    Shade(f32),
+}
+
+use Source::{Color, Scope, Shade};
+
+// (slot, accent hue, sources): the first source the theme defines wins. accent slots pass
+// over neutral candidates while a chromatic one follows, and over colors an earlier accent
+// took. the hue, in oklch degrees, picks a theme color when every source is taken. roles
+// from the base16 styling guide, https://github.com/tinted-theming/home/blob/main/styling.md
+const BASE16: &[(&str, Option<f32>, &[Source])] = &[
+    // default background
+    (
+        "base00",
+        None,
+        &[Color("editor.background"), Color("terminal.ansiBlack")],
+    ),
+    // lighter background: line highlight, status bars
+    (
+        "base01",
+        None,
+        &[
+            Color("editor.lineHighlightBackground"),
+            Color("input.background"),
+            Color("editorWidget.background"),
+            Shade(0.05),
+        ],
+    ),
+    // selection background
+    (
+        "base02",
+        None,
+        &[Color("editor.selectionBackground"), Shade(0.1)],
+    ),
+    // comments, invisibles
+    (
+        "base03",
+        None,
+        &[
+            Scope("comment"),
+            Color("editorLineNumber.foreground"),
+            Color("terminal.ansiBrightBlack"),
+            Shade(0.3),
+        ],
+    ),
+    // dark foreground: status bars
+    (
+        "base04",
+        None,
+        &[
+            Color("statusBar.foreground"),
+            Color("terminal.ansiWhite"),
+            Shade(0.6),
+        ],
+    ),
+    // default foreground, caret, delimiters, operators
+    (
+        "base05",
+        None,
+        &[Color("editor.foreground"), Color("foreground"), Shade(0.75)],
+    ),
+    // light foreground
+    (
+        "base06",
+        None,
+        &[Color("editorCursor.foreground"), Shade(0.8)],
+    ),
+    // lightest foreground
+    (
+        "base07",
+        None,
+        &[Color("terminal.ansiBrightWhite"), Shade(0.85)],
+    ),
+    // variables, tags, link text, diff deleted
+    (
+        "base08",
+        Some(29.0),
+        &[
+            Scope("variable"),
+            Scope("entity.name.tag"),
+            Scope("markup.underline.link"),
+            Color("gitDecoration.deletedResourceForeground"),
+            Color("editorError.foreground"),
+            Color("terminal.ansiRed"),
+        ],
+    ),
+    // integers, booleans, constants
+    (
+        "base09",
+        Some(55.0),
+        &[
+            Scope("constant.numeric"),
+            Scope("constant.language"),
+            Scope("constant"),
+            Color("editorWarning.foreground"),
+        ],
+    ),
+    // classes, markup bold, search background
+    (
+        "base0A",
+        Some(110.0),
+        &[
+            Scope("entity.name.type"),
+            Scope("entity.name.class"),
+            Scope("support.class"),
+            Scope("markup.bold"),
+            Color("terminal.ansiYellow"),
+        ],
+    ),
+    // strings, diff inserted
+    (
+        "base0B",
+        Some(142.0),
+        &[
+            Scope("string"),
+            Color("gitDecoration.addedResourceForeground"),
+            Color("terminal.ansiGreen"),
+        ],
+    ),
+    // support, regular expressions, escapes, quotes
+    (
+        "base0C",
+        Some(195.0),
+        &[
+            Scope("support"),
+            Scope("string.regexp"),
+            Scope("constant.character.escape"),
+            Scope("markup.quote"),
+            Color("terminal.ansiCyan"),
+        ],
+    ),
+    // functions, methods, headings
+    (
+        "base0D",
+        Some(264.0),
+        &[
+            Scope("entity.name.function"),
+            Scope("support.function"),
+            Scope("markup.heading"),
+            Color("terminal.ansiBlue"),
+        ],
+    ),
+    // keywords, storage, diff changed
+    (
+        "base0E",
+        Some(328.0),
+        &[
+            Scope("keyword"),
+            Scope("storage"),
+            Color("gitDecoration.modifiedResourceForeground"),
+            Color("terminal.ansiMagenta"),
+        ],
+    ),
+    // deprecated, embedded language tags
+    (
+        "base0F",
+        Some(40.0),
+        &[Scope("invalid.deprecated"), Scope("meta.embedded")],
+    ),
+];
+
+// (slot, base16 fallback, accent, sources): base24's extra backgrounds and bright colors.
+// a base16 scheme uses the fallback slot in their place
+const BASE24: &[(&str, &str, bool, &[Source])] = &[
+    (
+        "base10",
+        "base00",
+        false,
+        &[Color("sideBar.background"), Shade(-0.03)],
+    ),
+    (
+        "base11",
+        "base00",
+        false,
+        &[Color("activityBar.background"), Shade(-0.06)],
+    ),
+    ("base12", "base08", true, &[Color("terminal.ansiBrightRed")]),
+    (
+        "base13",
+        "base0A",
+        true,
+        &[Color("terminal.ansiBrightYellow")],
+    ),
+    (
+        "base14",
+        "base0B",
+        true,
+        &[Color("terminal.ansiBrightGreen")],
+    ),
+    (
+        "base15",
+        "base0C",
+        true,
+        &[Color("terminal.ansiBrightCyan")],
+    ),
+    (
+        "base16",
+        "base0D",
+        true,
+        &[Color("terminal.ansiBrightBlue")],
+    ),
+    (
+        "base17",
+        "base0E",
+        true,
+        &[Color("terminal.ansiBrightMagenta")],
+    ),
+];
+
+// imported manifests get oxocarbon's own `[compat]` groups, the ones oxocarbon-themec
+// falls back on, moved onto the scheme's backgrounds
+const OXOCARBON_MANIFEST: &str = include_str!("../../oxocarbon.toml");
+
+// the oxocarbon grays behind base00, base01 and base02
+const GRAY_SLOTS: [u8; 3] = [0x16, 0x26, 0x39];
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: json2base16 [--base24] [--author <name>] <input.json|-> <output.yaml|->\n       json2base16 --import <scheme.yaml|-> <output.toml|->"
+    );
+    process::exit(2);
+}
+
+fn main() -> io::Result<()> {
+    let mut base24 = false;
+    let mut import = false;
+    let mut author = String::from("Nyoom Engineering");
+    let mut paths = Vec::with_capacity(2);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base24" => base24 = true,
+            "--import" => import = true,
+            "--author" => author = args.next().unwrap_or_else(|| usage()),
+            _ => paths.push(arg),
+        }
+    }
+    let [input, output]: [String; 2] = paths.try_into().unwrap_or_else(|_| usage());
+
+    let mut src = String::new();
+    match input.as_str() {
+        "-" => io::stdin().lock().read_to_string(&mut src)?,
+        path => fs::File::open(path)?.read_to_string(&mut src)?,
+    };
+    let out = if import {
+        import_scheme(&src)?
+    } else {
+        export_scheme(&serde_json::from_str(&src)?, base24, &author)?
+    };
+
+    match output.as_str() {
+        "-" => io::stdout().lock().write_all(out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// compiled theme -> base16 (or base24) scheme yaml, in the tinted-theming 0.11 layout
+fn export_scheme(theme: &Value, base24: bool, author: &str) -> io::Result<String> {
+    let name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let tokens = theme["tokenColors"]
+        .as_array()
+        .ok_or_else(|| invalid("Missing tokenColors"))?;
+    let color = |key: &str| {
+        colors
+            .get(key)
+            .and_then(Value::as_str)
+            .and_then(parse_hex_color)
+    };
+    let (bg, _) = color("editor.background").ok_or_else(|| invalid("Missing editor.background"))?;
+    let fg = color("editor.foreground").map_or([255; 3], |(rgb, a)| composite_over(rgb, a, bg));
+    let is_light = luminance_from_u8(bg[0], bg[1], bg[2]) > 0.5;
+
+    let flatten = |hex: &str| parse_hex_color(hex).map(|(rgb, a)| composite_over(rgb, a, bg));
+    let is_accent = |rgb: &[u8; 3]| rgb_to_oklch(*rgb)[1] >= ACCENT_CHROMA;
+    // colors an accent slot can fall back on: syntax colors, then the terminal's
+    let pool: Vec<[u8; 3]> = tokens
+        .iter()
+        .filter_map(|item| item.get("settings")?.get("foreground")?.as_str())
+        .chain(
+            colors
+                .iter()
+                .filter(|(key, _)| key.starts_with("terminal.ansi"))
+                .filter_map(|(_, value)| value.as_str()),
+        )
+        .filter_map(flatten)
+        .filter(is_accent)
+        .collect();
+
+    let resolve = |slot: &str, accent: bool, sources: &[Source], taken: &[[u8; 3]]| {
+        let mut neutral = None;
+        let mut repeat = None;
+        for source in sources {
+            let rgb = match source {
+                Color(key) => color(key).map(|(rgb, a)| composite_over(rgb, a, bg)),
+                Scope(scope) => scope_foreground(tokens, scope).and_then(flatten),
+                Shade(delta) => Some(shade(bg, fg, *delta)),
+            };
+            // a slot that matches the background tells nothing apart
+            let Some(rgb) =
+                rgb.filter(|rgb| slot == "base00" || *rgb != bg || matches!(source, Shade(_)))
+            else {
+                continue;
+            };
+            if !accent {
+                return Ok(rgb);
+            }
+            if !is_accent(&rgb) {
+                neutral.get_or_insert(rgb);
+            } else if taken.contains(&rgb) {
+                repeat.get_or_insert(rgb);
+            } else {
+                return Ok(rgb);
+            }
+        }
+        Err(repeat.or(neutral).unwrap_or(fg))
+    };
+
+    // base16 accents stay distinct: a slot whose sources are all taken gets the unused
+    // theme color nearest its hue
+    let mut taken = Vec::new();
+    let mut slots: Vec<(&str, [u8; 3])> = Vec::with_capacity(BASE16.len() + BASE24.len());
+    for (slot, hue, sources) in BASE16 {
+        let rgb = match (resolve(slot, hue.is_some(), sources, &taken), hue) {
+            (Ok(rgb), _) => rgb,
+            (Err(fallback), Some(hue)) => pool
+                .iter()
+                .filter(|rgb| !taken.contains(*rgb))
+                .min_by(|a, b| hue_distance(a, *hue).total_cmp(&hue_distance(b, *hue)))
+                .copied()
+                .unwrap_or(fallback),
+            (Err(fallback), None) => fallback,
+        };
+        if hue.is_some() {
+            taken.push(rgb);
+        }
+        slots.push((slot, rgb));
+    }
+    if base24 {
+        slots.extend(BASE24.iter().map(|(slot, _, accent, sources)| {
+            (
+                *slot,
+                resolve(slot, *accent, sources, &[]).unwrap_or_else(|rgb| rgb),
+            )
+        }));
+    }
+
+    let mut out = format!(
+        "system: \"{}\"\nname: {}\nauthor: {}\nvariant: \"{}\"\npalette:\n",
+        if base24 { "base24" } else { "base16" },
+        quote(name),
+        quote(author),
+        if is_light { "light" } else { "dark" },
+    );
+    for (slot, rgb) in slots {
+        out.push_str(&format!("  {slot}: \"{}\"\n", format_hex_color(rgb, None)));
+    }
+    Ok(out)
+}
+
+/// base16/base24 scheme yaml, either layout -> manifest seeded through the same role table.
+/// the scheme becomes the manifest's `[palette]`, every mapped key refers to its slot
+fn import_scheme(src: &str) -> io::Result<String> {
+    let scheme: serde_yaml::Value =
+        serde_yaml::from_str(src).map_err(|e| invalid(&e.to_string()))?;
+    // tinted-theming 0.11 nests the slots under `palette`, the original format doesn't
+    let palette = scheme.get("palette").unwrap_or(&scheme);
+    let slot = |name: &str| {
+        let hex = palette.get(name)?.as_str()?;
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        parse_hex_color(&format!("#{hex}")).map(|(rgb, _)| rgb)
+    };
+    let name = scheme
+        .get("name")
+        .or_else(|| scheme.get("scheme"))
+        .and_then(serde_yaml::Value::as_str)
+        .ok_or_else(|| invalid("Missing name"))?;
+    let mut defined: Vec<(&str, [u8; 3])> = Vec::with_capacity(BASE16.len() + BASE24.len());
+    for (name, ..) in BASE16 {
+        let rgb = slot(name).ok_or_else(|| invalid(&format!("Missing {name}")))?;
+        defined.push((name, rgb));
+    }
+    for (name, ..) in BASE24 {
+        if let Some(rgb) = slot(name) {
+            defined.push((name, rgb));
+        }
+    }
+    let is_light = scheme
+        .get("variant")
+        .and_then(serde_yaml::Value::as_str)
+        .map_or_else(
+            || {
+                let [r, g, b] = defined[0].1;
+                luminance_from_u8(r, g, b) > 0.5
+            },
+            |v| v == "light",
+        );
+
+    let sources = BASE16
+        .iter()
+        .map(|(name, _, sources)| (*name, *sources))
+        .chain(BASE24.iter().map(|(name, fallback, _, sources)| {
+            let name = if defined.iter().any(|(d, _)| d == name) {
+                *name
+            } else {
+                *fallback
+            };
+            (name, *sources)
+        }));
+
+    // each key or scope goes to the first slot that lists it
+    let mut colors: Vec<(&str, &str)> = Vec::new();
+    let mut rules: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (slot, sources) in sources {
+        let mut scopes = Vec::new();
+        for source in sources {
+            match source {
+                Color(key) if !colors.iter().any(|(k, _)| k == key) => colors.push((key, slot)),
+                Scope(scope) if !rules.iter().any(|(_, s)| s.contains(scope)) => {
+                    scopes.push(*scope)
+                }
+                _ => {}
+            }
+        }
+        if !scopes.is_empty() {
+            rules.push((slot, scopes));
+        }
+    }
+
+    let mut out = format!(
+        "name = {}\ntype = \"{}\"\n\n[palette]\n",
+        quote(name),
+        if is_light { "light" } else { "dark" }
+    );
+    for (name, rgb) in &defined {
+        out.push_str(&format!("{name} = \"{}\"\n", format_hex_color(*rgb, None)));
+    }
+    out.push_str("\n[colors]\n");
+    for (key, slot) in colors {
+        let key = if key.contains('.') {
+            quote(key)
+        } else {
+            key.to_string()
+        };
+        out.push_str(&format!("{key} = \"${slot}\"\n"));
+    }
+    let manifest: toml::Table = toml::from_str(OXOCARBON_MANIFEST).expect("oxocarbon.toml parses");
+    let backgrounds = [defined[0].1, defined[1].1, defined[2].1];
+    for (group, table) in manifest["compat"].as_table().into_iter().flatten() {
+        let (Some(color), Some(keys)) = (
+            table.get("color").and_then(toml::Value::as_str),
+            table.get("keys").and_then(toml::Value::as_array),
+        ) else {
+            continue;
+        };
+        let color = remap_grays(color, |v| {
+            format_hex_color(scheme_gray(v, backgrounds, is_light), None)
+        });
+        let keys: Vec<String> = keys
+            .iter()
+            .filter_map(toml::Value::as_str)
+            .map(quote)
+            .collect();
+        out.push_str(&format!(
+            "\n[compat.{group}]\ncolor = {}\nkeys = [{}]\n",
+            quote(&color),
+            keys.join(", ")
+        ));
+    }
+    for (slot, scopes) in rules {
+        let scopes: Vec<String> = scopes.into_iter().map(quote).collect();
+        out.push_str(&format!(
+            "\n[[tokenColors]]\nscope = [{}]\nsettings = {{ foreground = \"${slot}\" }}\n",
+            scopes.join(", ")
+        ));
+    }
+    Ok(out)
+}
+
+// every oxocarbon gray `#vvvvvv` in a compat expression through `map`
+fn remap_grays(expr: &str, map: impl Fn(u8) -> String) -> String {
+    let mut out = String::with_capacity(expr.len());
+    let mut rest = expr;
+    while let Some(i) = rest.find('#') {
+        out.push_str(&rest[..i]);
+        let hex = rest.get(i..i + 7).unwrap_or(&rest[i..]);
+        match parse_hex_color(hex) {
+            Some(([r, g, b], None)) if r == g && g == b => out.push_str(&map(r)),
+            _ => out.push_str(hex),
+        }
+        rest = &rest[i + hex.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// the scheme color playing oxocarbon gray `v`: between two of its backgrounds, or past
+// base00 by the same share of the way to black (white in light schemes)
+fn scheme_gray(v: u8, backgrounds: [[u8; 3]; 3], is_light: bool) -> [u8; 3] {
+    let mix = |a: [u8; 3], b: [u8; 3], t: f32| {
+        std::array::from_fn(|i| {
+            (f32::from(a[i]) + (f32::from(b[i]) - f32::from(a[i])) * t).round() as u8
+        })
+    };
+    if v <= GRAY_SLOTS[0] {
+        let away = if is_light { [255; 3] } else { [0; 3] };
+        return mix(
+            backgrounds[0],
+            away,
+            1.0 - f32::from(v) / f32::from(GRAY_SLOTS[0]),
+        );
+    }
+    for (i, pair) in GRAY_SLOTS.windows(2).enumerate() {
+        if v <= pair[1] {
+            let t = f32::from(v - pair[0]) / f32::from(pair[1] - pair[0]);
+            return mix(backgrounds[i], backgrounds[i + 1], t);
+        }
+    }
+    backgrounds[2]
+}
+
+// json string escapes are valid in yaml and toml double-quoted strings alike
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+// degrees around the oklch hue circle between `rgb` and `hue`
+fn hue_distance(rgb: &[u8; 3], hue: f32) -> f32 {
+    let d = (rgb_to_oklch(*rgb)[2] - hue).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+fn shade(bg: [u8; 3], fg: [u8; 3], delta: f32) -> [u8; 3] {
+    let [l, c, h] = rgb_to_oklch(bg);
+    let toward = if rgb_to_oklch(fg)[0] >= l {
+        delta
+    } else {
+        -delta
+    };
+    oklch_to_rgb([(l + toward).clamp(0.0, 1.0), c, h])
+}
+
+// `scope` is an array in compiled oxocarbon themes, a comma separated string in others
+fn scopes(item: &Value) -> Vec<&str> {
+    match item.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::Array(arr)) => arr.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// the foreground textmate would pick for `scope`: longest matching selector, later rules
+// winning ties
+fn scope_foreground<'a>(tokens: &'a [Value], scope: &str) -> Option<&'a str> {
+    let scope_matches = |sel: &str| {
+        scope.starts_with(sel)
+            && (scope.len() == sel.len() || scope.as_bytes().get(sel.len()) == Some(&b'.'))
+    };
+    tokens
+        .iter()
+        .filter_map(|item| {
+            let fg = item.get("settings")?.get("foreground")?.as_str()?;
+            let len = scopes(item)
+                .into_iter()
+                .filter(|sel| scope_matches(sel))
+                .map(str::len)
+                .max()?;
+            Some((len, fg))
+        })
+        .fold(None, |best: Option<(usize, &str)>, (len, fg)| match best {
+            Some((best_len, _)) if best_len > len => best,
+            _ => Some((len, fg)),
+        })
+        .map(|(_, fg)| fg)
+}
@@ -0,0 +1,539 @@
+use oxocarbon_utils::{composite_over, parse_hex_rgba_u8 as parse_hex};
+use serde_json::{Map, Value};
+use std::{env, fmt::Write as _, fs, io, path::Path};
+
+/// the face attribute a vscode color sets
+enum Part {
+    Fg,
+    Bg,
+    /// a wavy underline in the color
+    Wave,
+}
+
+// (face, attribute, vscode color keys): the first key the theme sets wins
+const UI_FACES: &[(&str, Part, &[&str])] = &[
+    ("default", Part::Fg, &["editor.foreground", "foreground"]),
+    ("default", Part::Bg, &["editor.background"]),
+    ("cursor", Part::Bg, &["editorCursor.foreground"]),
+    (
+        "fringe",
+        Part::Bg,
+        &["editorGutter.background", "editor.background"],
+    ),
+    ("region", Part::Bg, &["editor.selectionBackground"]),
+    (
+        "secondary-selection",
+        Part::Bg,
+        &["editor.inactiveSelectionBackground"],
+    ),
+    (
+        "hl-line",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    ("highlight", Part::Bg, &["list.hoverBackground"]),
+    ("line-number", Part::Fg, &["editorLineNumber.foreground"]),
+    (
+        "line-number",
+        Part::Bg,
+        &["editorGutter.background", "editor.background"],
+    ),
+    (
+        "line-number-current-line",
+        Part::Fg,
+        &["editorLineNumber.activeForeground", "editor.foreground"],
+    ),
+    (
+        "line-number-current-line",
+        Part::Bg,
+        &["editorGutter.background", "editor.background"],
+    ),
+    ("mode-line", Part::Fg, &["statusBar.foreground"]),
+    ("mode-line", Part::Bg, &["statusBar.background"]),
+    ("mode-line-inactive", Part::Fg, &["tab.inactiveForeground"]),
+    ("mode-line-inactive", Part::Bg, &["statusBar.background"]),
+    ("header-line", Part::Fg, &["tab.activeForeground"]),
+    (
+        "header-line",
+        Part::Bg,
+        &["editorGroupHeader.tabsBackground"],
+    ),
+    (
+        "vertical-border",
+        Part::Fg,
+        &["editorGroup.border", "panel.border"],
+    ),
+    (
+        "window-divider",
+        Part::Fg,
+        &["editorGroup.border", "panel.border"],
+    ),
+    ("minibuffer-prompt", Part::Fg, &["textLink.foreground"]),
+    ("link", Part::Fg, &["textLink.foreground"]),
+    ("isearch", Part::Fg, &["editor.findMatchForeground"]),
+    ("isearch", Part::Bg, &["editor.findMatchBackground"]),
+    (
+        "lazy-highlight",
+        Part::Fg,
+        &["editor.findMatchHighlightForeground"],
+    ),
+    (
+        "lazy-highlight",
+        Part::Bg,
+        &["editor.findMatchHighlightBackground"],
+    ),
+    // editorBracketMatch.background is transparent in oxocarbon
+    (
+        "show-paren-match",
+        Part::Bg,
+        &["editor.selectionBackground"],
+    ),
+    (
+        "whitespace-space",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    (
+        "error",
+        Part::Fg,
+        &["editorError.foreground", "errorForeground"],
+    ),
+    ("warning", Part::Fg, &["editorWarning.foreground"]),
+    ("success", Part::Fg, &["editorGutter.addedBackground"]),
+    ("shadow", Part::Fg, &["disabledForeground"]),
+    ("tooltip", Part::Fg, &["editorHoverWidget.foreground"]),
+    ("tooltip", Part::Bg, &["editorHoverWidget.background"]),
+    ("flymake-error", Part::Wave, &["editorError.foreground"]),
+    ("flymake-warning", Part::Wave, &["editorWarning.foreground"]),
+    ("flymake-note", Part::Wave, &["editorInfo.foreground"]),
+    (
+        "diff-added",
+        Part::Bg,
+        &["diffEditor.insertedLineBackground"],
+    ),
+    (
+        "diff-removed",
+        Part::Bg,
+        &["diffEditor.removedLineBackground"],
+    ),
+    (
+        "diff-refine-added",
+        Part::Bg,
+        &["diffEditor.insertedTextBackground"],
+    ),
+    (
+        "diff-refine-removed",
+        Part::Bg,
+        &["diffEditor.removedTextBackground"],
+    ),
+    (
+        "diff-hl-insert",
+        Part::Fg,
+        &["editorGutter.addedBackground"],
+    ),
+    (
+        "diff-hl-insert",
+        Part::Bg,
+        &["editorGutter.addedBackground"],
+    ),
+    (
+        "diff-hl-change",
+        Part::Fg,
+        &["editorGutter.modifiedBackground"],
+    ),
+    (
+        "diff-hl-change",
+        Part::Bg,
+        &["editorGutter.modifiedBackground"],
+    ),
+    (
+        "diff-hl-delete",
+        Part::Fg,
+        &["editorGutter.deletedBackground"],
+    ),
+    (
+        "diff-hl-delete",
+        Part::Bg,
+        &["editorGutter.deletedBackground"],
+    ),
+    (
+        "magit-diff-added",
+        Part::Bg,
+        &["diffEditor.insertedLineBackground"],
+    ),
+    (
+        "magit-diff-added-highlight",
+        Part::Bg,
+        &["diffEditor.insertedTextBackground"],
+    ),
+    (
+        "magit-diff-removed",
+        Part::Bg,
+        &["diffEditor.removedLineBackground"],
+    ),
+    (
+        "magit-diff-removed-highlight",
+        Part::Bg,
+        &["diffEditor.removedTextBackground"],
+    ),
+    (
+        "magit-diff-context-highlight",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    (
+        "magit-section-highlight",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    (
+        "magit-diff-hunk-heading",
+        Part::Fg,
+        &["editorWidget.foreground"],
+    ),
+    (
+        "magit-diff-hunk-heading",
+        Part::Bg,
+        &["editorWidget.background"],
+    ),
+    (
+        "magit-diff-hunk-heading-highlight",
+        Part::Bg,
+        &["editor.selectionBackground"],
+    ),
+];
+
+// (face, scopes): each scope is resolved the way textmate would color it, the first scope
+// any rule covers wins. list the most specific scope first
+const SCOPE_FACES: &[(&str, &[&str])] = &[
+    ("font-lock-comment-face", &["comment"]),
+    (
+        "font-lock-comment-delimiter-face",
+        &["punctuation.definition.comment", "comment"],
+    ),
+    (
+        "font-lock-doc-face",
+        &["comment.block.documentation", "comment"],
+    ),
+    ("font-lock-string-face", &["string"]),
+    ("font-lock-keyword-face", &["keyword"]),
+    ("font-lock-builtin-face", &["support.function", "support"]),
+    ("font-lock-function-name-face", &["entity.name.function"]),
+    ("font-lock-variable-name-face", &["variable"]),
+    ("font-lock-type-face", &["entity.name.type", "entity"]),
+    ("font-lock-constant-face", &["constant.other", "constant"]),
+    ("font-lock-warning-face", &["invalid"]),
+    ("font-lock-negation-char-face", &["keyword.operator"]),
+    (
+        "font-lock-preprocessor-face",
+        &["keyword.control.directive", "preproc"],
+    ),
+    (
+        "font-lock-regexp-grouping-backslash",
+        &["constant.character.escape"],
+    ),
+    (
+        "font-lock-regexp-grouping-construct",
+        &["string.regexp", "string"],
+    ),
+    // tree-sitter faces, emacs 29+
+    (
+        "font-lock-bracket-face",
+        &["punctuation.section", "punctuation"],
+    ),
+    (
+        "font-lock-delimiter-face",
+        &["punctuation.separator", "punctuation"],
+    ),
+    ("font-lock-escape-face", &["constant.character.escape"]),
+    (
+        "font-lock-function-call-face",
+        &["entity.name.function.call", "entity.name.function"],
+    ),
+    ("font-lock-misc-punctuation-face", &["punctuation"]),
+    ("font-lock-number-face", &["constant.numeric", "constant"]),
+    ("font-lock-operator-face", &["keyword.operator"]),
+    (
+        "font-lock-property-name-face",
+        &["variable.member", "variable.other.property"],
+    ),
+    (
+        "font-lock-property-use-face",
+        &["variable.member", "variable.other.property"],
+    ),
+    ("font-lock-punctuation-face", &["punctuation"]),
+    ("font-lock-variable-use-face", &["variable"]),
+    (
+        "font-lock-doc-markup-face",
+        &["storage.type.class.jsdoc", "comment.block.documentation"],
+    ),
+];
+
+// (ansi-color face suffix, vscode color key), emacs 28+
+const TERMINAL_FACES: &[(&str, &str)] = &[
+    ("black", "terminal.ansiBlack"),
+    ("red", "terminal.ansiRed"),
+    ("green", "terminal.ansiGreen"),
+    ("yellow", "terminal.ansiYellow"),
+    ("blue", "terminal.ansiBlue"),
+    ("magenta", "terminal.ansiMagenta"),
+    ("cyan", "terminal.ansiCyan"),
+    ("white", "terminal.ansiWhite"),
+    ("bright-black", "terminal.ansiBrightBlack"),
+    ("bright-red", "terminal.ansiBrightRed"),
+    ("bright-green", "terminal.ansiBrightGreen"),
+    ("bright-yellow", "terminal.ansiBrightYellow"),
+    ("bright-blue", "terminal.ansiBrightBlue"),
+    ("bright-magenta", "terminal.ansiBrightMagenta"),
+    ("bright-cyan", "terminal.ansiBrightCyan"),
+    ("bright-white", "terminal.ansiBrightWhite"),
+];
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let name = match args.iter().position(|a| a == "--name") {
+        Some(i) if i + 1 < args.len() => {
+            let name = args.remove(i + 1);
+            args.remove(i);
+            Some(name)
+        }
+        Some(_) => usage(),
+        None => None,
+    };
+    if args.len() != 2 {
+        usage();
+    }
+
+    // emacs loads theme `name` from `name-theme.el`
+    let name = name
+        .or_else(|| {
+            let stem = Path::new(&args[1]).file_stem()?.to_str()?;
+            stem.strip_suffix("-theme").map(String::from)
+        })
+        .unwrap_or_else(|| "oxocarbon".to_string());
+
+    let reader: Box<dyn io::Read> = match args[0].as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+
+    let theme: Value = serde_json::from_reader(reader)?;
+    let out = deftheme(&theme, &name)?;
+
+    match args[1].as_str() {
+        "-" => io::Write::write_all(&mut io::stdout().lock(), out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: json2emacs [--name <theme>] <input.json|-> <output-theme.el|->");
+    std::process::exit(2);
+}
+
+#[derive(Default)]
+struct Face {
+    fg: Option<String>,
+    bg: Option<String>,
+    wave: Option<String>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl Face {
+    fn set(&mut self, part: &Part, color: String) {
+        match part {
+            Part::Fg => self.fg = Some(color),
+            Part::Bg => self.bg = Some(color),
+            Part::Wave => self.wave = Some(color),
+        }
+    }
+
+    fn set_font_style(&mut self, font_style: &str) {
+        for style in font_style.split_whitespace() {
+            match style.to_ascii_lowercase().as_str() {
+                "bold" => self.bold = true,
+                "italic" => self.italic = true,
+                "underline" => self.underline = true,
+                "strikethrough" => self.strikethrough = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn to_plist(&self) -> String {
+        let mut attrs = Vec::new();
+        if let Some(fg) = &self.fg {
+            attrs.push(format!(":foreground \"#{fg}\""));
+        }
+        if let Some(bg) = &self.bg {
+            attrs.push(format!(":background \"#{bg}\""));
+        }
+        if self.bold {
+            attrs.push(":weight bold".to_string());
+        }
+        if self.italic {
+            attrs.push(":slant italic".to_string());
+        }
+        if let Some(color) = &self.wave {
+            attrs.push(format!(":underline (:style wave :color \"#{color}\")"));
+        } else if self.underline {
+            attrs.push(":underline t".to_string());
+        }
+        if self.strikethrough {
+            attrs.push(":strike-through t".to_string());
+        }
+        attrs.join(" ")
+    }
+}
+
+fn deftheme(theme: &Value, name: &str) -> io::Result<String> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let theme_name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let tokens = theme["tokenColors"]
+        .as_array()
+        .ok_or_else(|| invalid("Missing tokenColors"))?;
+
+    let background = colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex)
+        .map(|(rgb, _)| rgb)
+        .ok_or_else(|| invalid("Invalid color: editor.background"))?;
+    // emacs has no alpha, translucent colors are flattened onto the editor background
+    let flatten = |hex: &str| {
+        parse_hex(hex).map(|(rgb, alpha)| {
+            let [r, g, b] = composite_over(rgb, alpha, background);
+            format!("{r:02x}{g:02x}{b:02x}")
+        })
+    };
+    let get_color = |keys: &[&str]| get_color(colors, keys).and_then(flatten);
+
+    // faces in the order they are written
+    let mut faces: Vec<(String, Face)> = Vec::new();
+    let face = |faces: &mut Vec<(String, Face)>, name: &str| -> usize {
+        faces
+            .iter()
+            .position(|(n, _)| n == name)
+            .unwrap_or_else(|| {
+                faces.push((name.to_string(), Face::default()));
+                faces.len() - 1
+            })
+    };
+
+    for (name, part, keys) in UI_FACES {
+        if let Some(color) = get_color(keys) {
+            let i = face(&mut faces, name);
+            faces[i].1.set(part, color);
+        }
+    }
+
+    for (name, scopes) in SCOPE_FACES {
+        // the font style comes from the same scope as the color
+        let Some((scope, fg)) = scopes
+            .iter()
+            .find_map(|s| Some((s, flatten(resolve(tokens, s, "foreground")?)?)))
+        else {
+            continue;
+        };
+        let i = face(&mut faces, name);
+        faces[i].1.fg = Some(fg);
+        if let Some(font_style) = resolve(tokens, scope, "fontStyle") {
+            faces[i].1.set_font_style(font_style);
+        }
+    }
+
+    for (suffix, key) in TERMINAL_FACES {
+        if let Some(color) = get_color(&[key]) {
+            let i = face(&mut faces, &format!("ansi-color-{suffix}"));
+            faces[i].1.fg = Some(color.clone());
+            faces[i].1.bg = Some(color);
+        }
+    }
+
+    let file = format!("{name}-theme.el");
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ";;; {file} --- {theme_name} -*- lexical-binding: t -*-\n"
+    );
+    out.push_str(";;; Commentary:\n\n;; generated by json2emacs\n\n;;; Code:\n\n");
+    let _ = writeln!(out, "(deftheme {name} \"{}\")\n", escape(theme_name));
+    let _ = write!(out, "(custom-theme-set-faces\n '{name}");
+    for (face, spec) in &faces {
+        let _ = write!(out, "\n '({face} ((t ({}))))", spec.to_plist());
+    }
+    out.push_str(")\n\n");
+    out.push_str(";;;###autoload\n(when load-file-name\n  (add-to-list 'custom-theme-load-path\n");
+    out.push_str(
+        "               (file-name-as-directory (file-name-directory load-file-name))))\n\n",
+    );
+    let _ = writeln!(out, "(provide-theme '{name})\n");
+    let _ = writeln!(out, ";;; {file} ends here");
+    Ok(out)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn get_color<'a>(colors: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| colors.get(*k).and_then(|v| v.as_str()))
+}
+
+// the `setting` textmate would give `scope`: longest matching selector among rules that
+// set it, later rules winning ties
+fn resolve<'a>(tokens: &'a [Value], scope: &str, setting: &str) -> Option<&'a str> {
+    let scope_matches = |pat: &str| {
+        scope.starts_with(pat)
+            && (scope.len() == pat.len() || scope.as_bytes().get(pat.len()) == Some(&b'.'))
+    };
+    tokens
+        .iter()
+        .filter_map(|item| {
+            let value = item.get("settings")?.get(setting)?.as_str()?;
+            let len = scopes(item)
+                .into_iter()
+                .filter(|s| scope_matches(s))
+                .map(str::len)
+                .max()?;
+            Some((len, value))
+        })
+        .fold(
+            None,
+            |best: Option<(usize, &str)>, (len, value)| match best {
+                Some((best_len, _)) if best_len > len => best,
+                _ => Some((len, value)),
+            },
+        )
+        .map(|(_, value)| value)
+}
+
+fn scopes(item: &Value) -> Vec<&str> {
+    match item.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::Array(arr)) => arr.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,635 @@
+use oxocarbon_utils::{composite_over, parse_hex_rgba_u8 as parse_hex, rgb_to_oklch};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    fmt::Write as _,
+    fs, io,
+};
+
+// below this oklch chroma a palette entry is named as a gray
+const NEUTRAL_CHROMA: f32 = 0.03;
+
+// upper oklch hue bound of each name, the last wraps around to the first
+const HUE_NAMES: [(f32, &str); 8] = [
+    (15.0, "magenta"),
+    (45.0, "red"),
+    (75.0, "orange"),
+    (120.0, "yellow"),
+    (170.0, "green"),
+    (220.0, "cyan"),
+    (275.0, "blue"),
+    (340.0, "purple"),
+];
+
+/// the part of a helix style a vscode color sets
+enum Part {
+    Fg,
+    Bg,
+}
+
+// (helix scope, part, vscode color keys): the first key the theme sets wins
+const UI_SCOPES: &[(&str, Part, &[&str])] = &[
+    ("ui.background", Part::Bg, &["editor.background"]),
+    ("ui.text", Part::Fg, &["editor.foreground", "foreground"]),
+    (
+        "ui.text.focus",
+        Part::Fg,
+        &["list.activeSelectionForeground"],
+    ),
+    ("ui.text.inactive", Part::Fg, &["disabledForeground"]),
+    ("ui.cursor", Part::Fg, &["editor.background"]),
+    ("ui.cursor", Part::Bg, &["editorLineNumber.foreground"]),
+    ("ui.cursor.primary", Part::Fg, &["editor.background"]),
+    ("ui.cursor.primary", Part::Bg, &["editorCursor.foreground"]),
+    // editorBracketMatch.background is transparent in oxocarbon
+    ("ui.cursor.match", Part::Bg, &["editor.selectionBackground"]),
+    (
+        "ui.cursorline.primary",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    (
+        "ui.selection",
+        Part::Bg,
+        &["editor.inactiveSelectionBackground"],
+    ),
+    (
+        "ui.selection.primary",
+        Part::Bg,
+        &["editor.selectionBackground"],
+    ),
+    ("ui.linenr", Part::Fg, &["editorLineNumber.foreground"]),
+    (
+        "ui.linenr.selected",
+        Part::Fg,
+        &["editorLineNumber.activeForeground", "editor.foreground"],
+    ),
+    (
+        "ui.gutter",
+        Part::Bg,
+        &["editorGutter.background", "editor.background"],
+    ),
+    ("ui.statusline", Part::Fg, &["statusBar.foreground"]),
+    ("ui.statusline", Part::Bg, &["statusBar.background"]),
+    (
+        "ui.statusline.inactive",
+        Part::Fg,
+        &["tab.inactiveForeground"],
+    ),
+    (
+        "ui.statusline.inactive",
+        Part::Bg,
+        &["statusBar.background"],
+    ),
+    ("ui.popup", Part::Fg, &["editorWidget.foreground"]),
+    ("ui.popup", Part::Bg, &["editorWidget.background"]),
+    ("ui.help", Part::Fg, &["editorHoverWidget.foreground"]),
+    ("ui.help", Part::Bg, &["editorHoverWidget.background"]),
+    ("ui.menu", Part::Fg, &["editorSuggestWidget.foreground"]),
+    ("ui.menu", Part::Bg, &["editorSuggestWidget.background"]),
+    (
+        "ui.menu.selected",
+        Part::Fg,
+        &["editorSuggestWidget.selectedForeground"],
+    ),
+    (
+        "ui.menu.selected",
+        Part::Bg,
+        &["editorSuggestWidget.selectedBackground"],
+    ),
+    (
+        "ui.window",
+        Part::Fg,
+        &["editorGroup.border", "panel.border"],
+    ),
+    ("ui.bufferline", Part::Fg, &["tab.inactiveForeground"]),
+    ("ui.bufferline", Part::Bg, &["tab.inactiveBackground"]),
+    ("ui.bufferline.active", Part::Fg, &["tab.activeForeground"]),
+    ("ui.bufferline.active", Part::Bg, &["tab.activeBackground"]),
+    (
+        "ui.bufferline.background",
+        Part::Bg,
+        &["editorGroupHeader.tabsBackground"],
+    ),
+    (
+        "ui.virtual.whitespace",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    (
+        "ui.virtual.indent-guide",
+        Part::Fg,
+        &[
+            "editorIndentGuide.background1",
+            "editorIndentGuide.background",
+        ],
+    ),
+    ("ui.virtual.ruler", Part::Bg, &["editorRuler.foreground"]),
+    (
+        "ui.virtual.inlay-hint",
+        Part::Fg,
+        &["editorInlayHint.foreground"],
+    ),
+    (
+        "ui.virtual.inlay-hint",
+        Part::Bg,
+        &["editorInlayHint.background"],
+    ),
+    (
+        "ui.highlight",
+        Part::Bg,
+        &["editor.wordHighlightStrongBackground"],
+    ),
+    ("diff.plus", Part::Fg, &["editorGutter.addedBackground"]),
+    ("diff.minus", Part::Fg, &["editorGutter.deletedBackground"]),
+    ("diff.delta", Part::Fg, &["editorGutter.modifiedBackground"]),
+];
+
+// (severity, vscode color keys): each sets the severity's scope and its curly underline
+const DIAGNOSTICS: &[(&str, &[&str])] = &[
+    ("error", &["editorError.foreground", "errorForeground"]),
+    ("warning", &["editorWarning.foreground"]),
+    ("info", &["editorInfo.foreground"]),
+    ("hint", &["editorHint.foreground", "editorInfo.foreground"]),
+];
+
+// (helix scope, textmate scopes): each scope is resolved the way textmate would color it,
+// the first scope any rule covers wins. list the most specific scope first
+const SYNTAX: &[(&str, &[&str])] = &[
+    ("attribute", &["entity.other.attribute-name"]),
+    ("type", &["entity.name.type", "entity"]),
+    ("type.builtin", &["support.type", "storage.type"]),
+    (
+        "constructor",
+        &["entity.name.function.constructor", "support.class"],
+    ),
+    ("constant", &["constant.other", "constant"]),
+    ("constant.builtin", &["constant.language"]),
+    (
+        "constant.builtin.boolean",
+        &["constant.language.boolean", "constant.language"],
+    ),
+    ("constant.character", &["constant.character", "string"]),
+    ("constant.character.escape", &["constant.character.escape"]),
+    ("constant.numeric", &["constant.numeric", "constant"]),
+    ("string", &["string"]),
+    ("string.regexp", &["string.regexp", "string"]),
+    ("string.special.url", &["markup.underline.link"]),
+    ("comment", &["comment"]),
+    (
+        "comment.block.documentation",
+        &["comment.block.documentation", "comment"],
+    ),
+    ("variable", &["variable"]),
+    ("variable.builtin", &["variable.language", "variable"]),
+    ("variable.parameter", &["variable.parameter"]),
+    (
+        "variable.other.member",
+        &["variable.member", "variable.other.property"],
+    ),
+    ("label", &["entity.name.label", "label"]),
+    ("punctuation", &["punctuation"]),
+    (
+        "punctuation.delimiter",
+        &["punctuation.separator", "punctuation"],
+    ),
+    (
+        "punctuation.bracket",
+        &["punctuation.section", "punctuation"],
+    ),
+    (
+        "punctuation.special",
+        &["punctuation.section.embedded", "punctuation.definition"],
+    ),
+    ("keyword", &["keyword"]),
+    ("keyword.control", &["keyword.control", "keyword"]),
+    (
+        "keyword.control.conditional",
+        &["keyword.control.conditional", "keyword.control"],
+    ),
+    (
+        "keyword.control.repeat",
+        &["keyword.control.loop", "keyword.control"],
+    ),
+    (
+        "keyword.control.import",
+        &["keyword.control.import", "storage.modifier.import"],
+    ),
+    (
+        "keyword.control.return",
+        &["keyword.control.return", "keyword.control"],
+    ),
+    (
+        "keyword.control.exception",
+        &["keyword.control.exception", "keyword.control"],
+    ),
+    (
+        "keyword.operator",
+        &["keyword.operator.word", "keyword.operator"],
+    ),
+    (
+        "keyword.directive",
+        &["keyword.control.directive", "preproc"],
+    ),
+    (
+        "keyword.function",
+        &["keyword.other.fn", "storage.type.function", "keyword"],
+    ),
+    ("keyword.storage", &["storage"]),
+    ("keyword.storage.type", &["storage.type"]),
+    ("keyword.storage.modifier", &["storage.modifier"]),
+    ("operator", &["keyword.operator"]),
+    ("function", &["entity.name.function"]),
+    ("function.builtin", &["support.function"]),
+    (
+        "function.method",
+        &["entity.name.function.member", "entity.name.function"],
+    ),
+    (
+        "function.macro",
+        &["entity.name.function.macro", "entity.name.function"],
+    ),
+    ("tag", &["entity.name.tag"]),
+    (
+        "namespace",
+        &["entity.name.namespace", "entity.name.type.module"],
+    ),
+    ("special", &["support"]),
+    (
+        "markup.heading",
+        &["markup.heading", "entity.name.section.markdown"],
+    ),
+    (
+        "markup.list",
+        &["punctuation.definition.list", "markup.list"],
+    ),
+    ("markup.bold", &["markup.bold"]),
+    ("markup.italic", &["markup.italic"]),
+    ("markup.strikethrough", &["markup.strikethrough"]),
+    ("markup.link.url", &["markup.underline.link"]),
+    (
+        "markup.link.text",
+        &["string.other.link", "markup.underline.link"],
+    ),
+    ("markup.quote", &["markup.quote"]),
+    (
+        "markup.raw",
+        &["markup.inline.raw.string.markdown", "markup.raw"],
+    ),
+];
+
+fn usage() -> ! {
+    eprintln!("Usage: json2helix [--inherits <name>=<base.json>] <input.json|-> <output.toml|->");
+    std::process::exit(2);
+}
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let inherits = match args.iter().position(|a| a == "--inherits") {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            let Some((name, path)) = value.split_once('=') else {
+                usage();
+            };
+            Some((name.to_string(), path.to_string()))
+        }
+        Some(_) => usage(),
+        None => None,
+    };
+    if args.len() != 2 {
+        usage();
+    }
+
+    let reader: Box<dyn io::Read> = match args[0].as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+    let theme: Value = serde_json::from_reader(reader)?;
+    let name = theme["name"]
+        .as_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing name"))?;
+    let theme = Theme::from_vscode(&theme)?;
+
+    let out = match inherits {
+        Some((parent_name, path)) => {
+            let parent: Value = serde_json::from_reader(fs::File::open(path)?)?;
+            theme.write(name, Some((&parent_name, &Theme::from_vscode(&parent)?)))
+        }
+        None => theme.write(name, None),
+    };
+
+    match args[1].as_str() {
+        "-" => io::Write::write_all(&mut io::stdout().lock(), out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+#[derive(Default)]
+struct Style {
+    fg: Option<[u8; 3]>,
+    bg: Option<[u8; 3]>,
+    modifiers: Vec<&'static str>,
+    // color (the foreground when unset) and style
+    underline: Option<(Option<[u8; 3]>, &'static str)>,
+}
+
+impl Style {
+    fn set_font_style(&mut self, font_style: &str) {
+        for style in font_style.split_whitespace() {
+            match style.to_ascii_lowercase().as_str() {
+                "bold" => self.modifiers.push("bold"),
+                "italic" => self.modifiers.push("italic"),
+                "strikethrough" => self.modifiers.push("crossed_out"),
+                "underline" => self.underline = Some((None, "line")),
+                _ => {}
+            }
+        }
+    }
+
+    fn colors(&self) -> impl Iterator<Item = [u8; 3]> {
+        [
+            self.fg,
+            self.bg,
+            self.underline.and_then(|(color, _)| color),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    fn to_toml(&self, palette: &Palette) -> String {
+        let mut fields = Vec::new();
+        if let Some(fg) = self.fg {
+            fields.push(format!("fg = \"{}\"", palette.name(fg)));
+        }
+        if let Some(bg) = self.bg {
+            fields.push(format!("bg = \"{}\"", palette.name(bg)));
+        }
+        if let Some((color, style)) = self.underline {
+            match color {
+                Some(color) => fields.push(format!(
+                    "underline = {{ color = \"{}\", style = \"{style}\" }}",
+                    palette.name(color)
+                )),
+                None => fields.push(format!("underline = {{ style = \"{style}\" }}")),
+            }
+        }
+        if !self.modifiers.is_empty() {
+            let modifiers: Vec<_> = self.modifiers.iter().map(|m| format!("\"{m}\"")).collect();
+            fields.push(format!("modifiers = [{}]", modifiers.join(", ")));
+        }
+        if fields.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+struct Theme {
+    // scopes in the order they're written
+    styles: Vec<(String, Style)>,
+    palette: Palette,
+}
+
+impl Theme {
+    fn from_vscode(theme: &Value) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let colors = theme["colors"]
+            .as_object()
+            .ok_or_else(|| invalid("Missing colors"))?;
+        let tokens = theme["tokenColors"]
+            .as_array()
+            .ok_or_else(|| invalid("Missing tokenColors"))?;
+        let background = colors
+            .get("editor.background")
+            .and_then(|v| v.as_str())
+            .and_then(parse_hex)
+            .map(|(rgb, _)| rgb)
+            .ok_or_else(|| invalid("Invalid color: editor.background"))?;
+        // helix has no alpha, translucent colors are flattened onto the editor background
+        let flatten =
+            |hex: &str| parse_hex(hex).map(|(rgb, alpha)| composite_over(rgb, alpha, background));
+        let get_color = |keys: &[&str]| get_color(colors, keys).and_then(flatten);
+
+        let mut styles: Vec<(String, Style)> = Vec::new();
+        let style = |styles: &mut Vec<(String, Style)>, scope: &str| -> usize {
+            styles
+                .iter()
+                .position(|(s, _)| s == scope)
+                .unwrap_or_else(|| {
+                    styles.push((scope.to_string(), Style::default()));
+                    styles.len() - 1
+                })
+        };
+
+        for (scope, scopes) in SYNTAX {
+            // the font style comes from the same scope as the color
+            let Some((tm_scope, fg)) = scopes
+                .iter()
+                .find_map(|s| Some((s, flatten(resolve(tokens, s, "foreground")?)?)))
+            else {
+                continue;
+            };
+            let i = style(&mut styles, scope);
+            styles[i].1.fg = Some(fg);
+            if let Some(font_style) = resolve(tokens, tm_scope, "fontStyle") {
+                styles[i].1.set_font_style(font_style);
+            }
+        }
+
+        for (scope, part, keys) in UI_SCOPES {
+            if let Some(color) = get_color(keys) {
+                let i = style(&mut styles, scope);
+                match part {
+                    Part::Fg => styles[i].1.fg = Some(color),
+                    Part::Bg => styles[i].1.bg = Some(color),
+                }
+            }
+        }
+
+        for (severity, keys) in DIAGNOSTICS {
+            let Some(color) = get_color(keys) else {
+                continue;
+            };
+            let i = style(&mut styles, severity);
+            styles[i].1.fg = Some(color);
+            let i = style(&mut styles, &format!("diagnostic.{severity}"));
+            styles[i].1.underline = Some((Some(color), "curl"));
+        }
+
+        let palette = Palette::infer(styles.iter().flat_map(|(_, s)| s.colors()));
+        Ok(Self { styles, palette })
+    }
+
+    // with a parent only what differs from it is written, anything the parent sets that
+    // this theme doesn't is cleared
+    fn write(&self, name: &str, parent: Option<(&str, &Theme)>) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {name}, generated by json2helix");
+
+        let parent_styles: HashMap<&str, String> = parent
+            .map(|(_, p)| {
+                p.styles
+                    .iter()
+                    .map(|(scope, style)| (scope.as_str(), style.to_toml(&p.palette)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some((parent_name, _)) = parent {
+            let _ = writeln!(out, "inherits = \"{parent_name}\"");
+        }
+        out.push('\n');
+
+        for (scope, style) in &self.styles {
+            let style = style.to_toml(&self.palette);
+            if parent_styles.get(scope.as_str()) != Some(&style) {
+                let _ = writeln!(out, "\"{scope}\" = {style}");
+            }
+        }
+        if let Some((_, p)) = parent {
+            for (scope, _) in &p.styles {
+                if !self.styles.iter().any(|(s, _)| s == scope) {
+                    let _ = writeln!(out, "\"{scope}\" = {{}}");
+                }
+            }
+        }
+
+        let parent_palette: BTreeMap<&str, [u8; 3]> = parent
+            .map(|(_, p)| {
+                p.palette
+                    .entries
+                    .iter()
+                    .map(|(name, rgb)| (name.as_str(), *rgb))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let entries: Vec<_> = self
+            .palette
+            .entries
+            .iter()
+            .filter(|(name, rgb)| parent_palette.get(name.as_str()) != Some(rgb))
+            .collect();
+        if !entries.is_empty() {
+            out.push_str("\n[palette]\n");
+            for (name, [r, g, b]) in entries {
+                let _ = writeln!(out, "{name} = \"#{r:02x}{g:02x}{b:02x}\"");
+            }
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct Palette {
+    // name and color, grays dark to light then accents around the hue wheel
+    entries: Vec<(String, [u8; 3])>,
+}
+
+impl Palette {
+    fn infer(colors: impl Iterator<Item = [u8; 3]>) -> Self {
+        let mut distinct: Vec<[u8; 3]> = Vec::new();
+        for rgb in colors {
+            if !distinct.contains(&rgb) {
+                distinct.push(rgb);
+            }
+        }
+        distinct.sort_by(|a, b| {
+            sort_key(*a)
+                .partial_cmp(&sort_key(*b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut palette = Self::default();
+        let mut taken: HashMap<String, usize> = HashMap::new();
+        for rgb in distinct {
+            let base = color_name(rgb);
+            let n = taken.entry(base.clone()).or_insert(0);
+            *n += 1;
+            let name = if *n == 1 { base } else { format!("{base}-{n}") };
+            palette.entries.push((name, rgb));
+        }
+        palette
+    }
+
+    fn name(&self, rgb: [u8; 3]) -> &str {
+        self.entries
+            .iter()
+            .find(|(_, c)| *c == rgb)
+            .map_or("", |(name, _)| name.as_str())
+    }
+}
+
+fn sort_key(rgb: [u8; 3]) -> (u8, f32, f32) {
+    let [l, c, h] = rgb_to_oklch(rgb);
+    if c < NEUTRAL_CHROMA {
+        (0, l, 0.0)
+    } else {
+        (1, h, l)
+    }
+}
+
+// the hue (or gray) and how dark it is from 0 to 100, e.g. `gray81` for #161616
+fn color_name(rgb: [u8; 3]) -> String {
+    let [l, c, h] = rgb_to_oklch(rgb);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let step = ((1.0 - l.clamp(0.0, 1.0)) * 100.0).round() as u8;
+    let hue = if c < NEUTRAL_CHROMA {
+        "gray"
+    } else {
+        HUE_NAMES
+            .iter()
+            .find(|(bound, _)| h < *bound)
+            .map_or(HUE_NAMES[0].1, |(_, name)| name)
+    };
+    format!("{hue}{step}")
+}
+
+fn get_color<'a>(colors: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| colors.get(*k).and_then(|v| v.as_str()))
+}
+
+// the `setting` textmate would give `scope`: longest matching selector among rules that
+// set it, later rules winning ties
+fn resolve<'a>(tokens: &'a [Value], scope: &str, setting: &str) -> Option<&'a str> {
+    let scope_matches = |pat: &str| {
+        scope.starts_with(pat)
+            && (scope.len() == pat.len() || scope.as_bytes().get(pat.len()) == Some(&b'.'))
+    };
+    tokens
+        .iter()
+        .filter_map(|item| {
+            let value = item.get("settings")?.get(setting)?.as_str()?;
+            let len = scopes(item)
+                .into_iter()
+                .filter(|s| scope_matches(s))
+                .map(str::len)
+                .max()?;
+            Some((len, value))
+        })
+        .fold(
+            None,
+            |best: Option<(usize, &str)>, (len, value)| match best {
+                Some((best_len, _)) if best_len > len => best,
+                _ => Some((len, value)),
+            },
+        )
+        .map(|(_, value)| value)
+}
+
+fn scopes(item: &Value) -> Vec<&str> {
+    match item.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::Array(arr)) => arr.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
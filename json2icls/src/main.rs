@@ -0,0 +1,564 @@
+use oxocarbon_utils::{composite_over, luminance_from_u8, parse_hex_rgba_u8 as parse_hex};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, env, fmt::Write as _, fs, io, path::Path};
+
+mod plugin;
+
+fn main() -> io::Result<()> {
+    let args: Vec<_> = env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "--plugin") {
+        let (version, inputs) = match args.get(2).map(String::as_str) {
+            Some("--version") => (args.get(3).map_or("", String::as_str), args.get(4..)),
+            _ => ("1.0.0", args.get(2..)),
+        };
+        let (Some(dir), Some(inputs)) = (args.get(1), inputs.filter(|i| !i.is_empty())) else {
+            eprintln!("Usage: json2icls --plugin <dir> [--version <version>] <input.json>...");
+            std::process::exit(2);
+        };
+        return plugin::write(Path::new(dir), inputs, version);
+    }
+    if args.len() != 2 {
+        eprintln!("Usage: json2icls <input.json|-> <output.icls|->");
+        eprintln!("       json2icls --plugin <dir> [--version <version>] <input.json>...");
+        std::process::exit(2);
+    }
+
+    let reader: Box<dyn io::Read> = match args[0].as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+
+    let theme: serde_json::Value = serde_json::from_reader(reader)?;
+    let out = icls(&theme)?;
+
+    match args[1].as_str() {
+        "-" => io::Write::write_all(&mut io::stdout().lock(), out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn editor_background(colors: &Map<String, Value>) -> io::Result<[u8; 3]> {
+    colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex)
+        .map(|(rgb, _)| rgb)
+        .ok_or_else(|| invalid("Invalid color: editor.background"))
+}
+
+// jetbrains colors have no alpha, translucent colors are flattened onto the editor background
+fn flatten(hex: &str, background: [u8; 3]) -> Option<String> {
+    parse_hex(hex).map(|(rgb, alpha)| {
+        let [r, g, b] = composite_over(rgb, alpha, background);
+        format!("{r:02x}{g:02x}{b:02x}")
+    })
+}
+
+fn is_light(background: [u8; 3]) -> bool {
+    let [r, g, b] = background;
+    luminance_from_u8(r, g, b) > 0.5
+}
+
+fn icls(theme: &Value) -> io::Result<String> {
+    let name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let tokens = theme["tokenColors"]
+        .as_array()
+        .ok_or_else(|| invalid("Missing tokenColors"))?;
+
+    let background = editor_background(colors)?;
+    let flatten = |hex: &str| flatten(hex, background);
+    let get_color = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| colors.get(*k).and_then(|v| v.as_str()).and_then(flatten))
+    };
+
+    let mut attributes: BTreeMap<&str, Attribute> = BTreeMap::new();
+    for (key, part, keys) in UI_ATTRIBUTES {
+        if let Some(color) = get_color(keys) {
+            attributes.entry(key).or_default().set(part, color);
+        }
+    }
+
+    // color and style come from the same pattern, the first the theme styles at all. a
+    // pattern with only a style keeps the parent scheme's color
+    for (key, pats) in SCOPE_MAPPINGS {
+        let Some((fg, font_style)) = pats.iter().find_map(|p| {
+            let fg = resolve(tokens, p, "foreground");
+            let font_style = resolve(tokens, p, "fontStyle");
+            (fg.is_some() || font_style.is_some()).then_some((fg, font_style))
+        }) else {
+            continue;
+        };
+        let attribute = attributes.entry(key).or_default();
+        if let Some(fg) = fg.and_then(flatten) {
+            attribute.foreground = Some(fg);
+        }
+        attribute.font_type = font_type(font_style.unwrap_or_default());
+    }
+
+    let parent = if is_light(background) {
+        "Default"
+    } else {
+        "Darcula"
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<scheme name=\"{}\" version=\"142\" parent_scheme=\"{parent}\">",
+        escape(name)
+    );
+    out.push_str("  <colors>\n");
+    for (key, keys) in EDITOR_COLORS {
+        if let Some(color) = get_color(keys) {
+            let _ = writeln!(out, "    <option name=\"{key}\" value=\"{color}\" />");
+        }
+    }
+    out.push_str("  </colors>\n  <attributes>\n");
+    for (key, attribute) in &attributes {
+        let _ = writeln!(out, "    <option name=\"{key}\">\n      <value>");
+        for (option, value) in attribute.options() {
+            let _ = writeln!(
+                out,
+                "        <option name=\"{option}\" value=\"{value}\" />"
+            );
+        }
+        out.push_str("      </value>\n    </option>\n");
+    }
+    out.push_str("  </attributes>\n</scheme>\n");
+    Ok(out)
+}
+
+/// the part of a text attribute a vscode color sets
+enum Part {
+    Foreground,
+    Background,
+    /// an effect color, with the effect: 1 underscore, 2 wave underscore
+    Effect(u8),
+}
+
+#[derive(Default)]
+struct Attribute {
+    foreground: Option<String>,
+    background: Option<String>,
+    effect: Option<(String, u8)>,
+    font_type: u8,
+}
+
+impl Attribute {
+    fn set(&mut self, part: &Part, color: String) {
+        match part {
+            Part::Foreground => self.foreground = Some(color),
+            Part::Background => self.background = Some(color),
+            Part::Effect(kind) => self.effect = Some((color, *kind)),
+        }
+    }
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = Vec::with_capacity(5);
+        if let Some(fg) = &self.foreground {
+            options.push(("FOREGROUND", fg.clone()));
+        }
+        if let Some(bg) = &self.background {
+            options.push(("BACKGROUND", bg.clone()));
+        }
+        if self.font_type != 0 {
+            options.push(("FONT_TYPE", self.font_type.to_string()));
+        }
+        if let Some((color, kind)) = &self.effect {
+            options.push(("EFFECT_COLOR", color.clone()));
+            options.push(("EFFECT_TYPE", kind.to_string()));
+        }
+        options
+    }
+}
+
+// FONT_TYPE is a bit set: 1 bold, 2 italic
+fn font_type(font_style: &str) -> u8 {
+    font_style
+        .split_whitespace()
+        .map(|s| match s.to_ascii_lowercase().as_str() {
+            "bold" => 1,
+            "italic" => 2,
+            _ => 0,
+        })
+        .fold(0, |acc, bit| acc | bit)
+}
+
+// the `setting` textmate would give `scope`: longest matching selector among rules that
+// set it, later rules winning ties
+fn resolve<'a>(tokens: &'a [Value], scope: &str, setting: &str) -> Option<&'a str> {
+    let scope_matches = |pat: &str| {
+        scope.starts_with(pat)
+            && (scope.len() == pat.len() || scope.as_bytes().get(pat.len()) == Some(&b'.'))
+    };
+    tokens
+        .iter()
+        .filter_map(|item| {
+            let value = item.get("settings")?.get(setting)?.as_str()?;
+            let len = scopes(item)
+                .into_iter()
+                .filter(|s| scope_matches(s))
+                .map(str::len)
+                .max()?;
+            Some((len, value))
+        })
+        .fold(
+            None,
+            |best: Option<(usize, &str)>, (len, value)| match best {
+                Some((best_len, _)) if best_len > len => best,
+                _ => Some((len, value)),
+            },
+        )
+        .map(|(_, value)| value)
+}
+
+fn scopes(item: &Value) -> Vec<&str> {
+    match item.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::Array(arr)) => arr.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const EDITOR_COLORS: &[(&str, &[&str])] = &[
+    ("CARET_COLOR", &["editorCursor.foreground"]),
+    (
+        "CARET_ROW_COLOR",
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    (
+        "GUTTER_BACKGROUND",
+        &["editorGutter.background", "editor.background"],
+    ),
+    ("LINE_NUMBERS_COLOR", &["editorLineNumber.foreground"]),
+    (
+        "LINE_NUMBER_ON_CARET_ROW_COLOR",
+        &["editorLineNumber.activeForeground"],
+    ),
+    ("SELECTION_BACKGROUND", &["editor.selectionBackground"]),
+    ("SELECTION_FOREGROUND", &["editor.selectionForeground"]),
+    (
+        "INDENT_GUIDE",
+        &[
+            "editorIndentGuide.background1",
+            "editorIndentGuide.background",
+        ],
+    ),
+    (
+        "SELECTED_INDENT_GUIDE",
+        &[
+            "editorIndentGuide.activeBackground1",
+            "editorIndentGuide.activeBackground",
+        ],
+    ),
+    ("WHITESPACES", &["editorWhitespace.foreground"]),
+    ("RIGHT_MARGIN_COLOR", &["editorRuler.foreground"]),
+    ("ADDED_LINES_COLOR", &["editorGutter.addedBackground"]),
+    ("MODIFIED_LINES_COLOR", &["editorGutter.modifiedBackground"]),
+    ("DELETED_LINES_COLOR", &["editorGutter.deletedBackground"]),
+    ("DOCUMENTATION_COLOR", &["editorHoverWidget.background"]),
+    (
+        "CONSOLE_BACKGROUND_KEY",
+        &["terminal.background", "panel.background"],
+    ),
+];
+
+const UI_ATTRIBUTES: &[(&str, Part, &[&str])] = &[
+    ("TEXT", Part::Foreground, &["editor.foreground"]),
+    ("TEXT", Part::Background, &["editor.background"]),
+    (
+        "ERRORS_ATTRIBUTES",
+        Part::Effect(2),
+        &["editorError.foreground"],
+    ),
+    (
+        "WARNING_ATTRIBUTES",
+        Part::Effect(2),
+        &["editorWarning.foreground"],
+    ),
+    (
+        "INFO_ATTRIBUTES",
+        Part::Effect(2),
+        &["editorInfo.foreground"],
+    ),
+    (
+        "HYPERLINK_ATTRIBUTES",
+        Part::Foreground,
+        &["textLink.foreground"],
+    ),
+    (
+        "HYPERLINK_ATTRIBUTES",
+        Part::Effect(1),
+        &["textLink.foreground"],
+    ),
+    (
+        "SEARCH_RESULT_ATTRIBUTES",
+        Part::Background,
+        &["editor.findMatchHighlightBackground"],
+    ),
+    (
+        "TEXT_SEARCH_RESULT_ATTRIBUTES",
+        Part::Background,
+        &["editor.findMatchBackground"],
+    ),
+    (
+        "IDENTIFIER_UNDER_CARET_ATTRIBUTES",
+        Part::Background,
+        &["editor.wordHighlightBackground"],
+    ),
+    (
+        "WRITE_IDENTIFIER_UNDER_CARET_ATTRIBUTES",
+        Part::Background,
+        &["editor.wordHighlightStrongBackground"],
+    ),
+    (
+        "MATCHED_BRACE_ATTRIBUTES",
+        Part::Background,
+        &["editorBracketMatch.background"],
+    ),
+    (
+        "DIFF_INSERTED",
+        Part::Background,
+        &[
+            "diffEditor.insertedLineBackground",
+            "diffEditor.insertedTextBackground",
+        ],
+    ),
+    (
+        "DIFF_DELETED",
+        Part::Background,
+        &[
+            "diffEditor.removedLineBackground",
+            "diffEditor.removedTextBackground",
+        ],
+    ),
+    (
+        "CONSOLE_NORMAL_OUTPUT",
+        Part::Foreground,
+        &["terminal.foreground"],
+    ),
+    (
+        "CONSOLE_ERROR_OUTPUT",
+        Part::Foreground,
+        &["editorError.foreground"],
+    ),
+    (
+        "CONSOLE_BLACK_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBlack"],
+    ),
+    (
+        "CONSOLE_RED_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiRed"],
+    ),
+    (
+        "CONSOLE_GREEN_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiGreen"],
+    ),
+    (
+        "CONSOLE_YELLOW_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiYellow"],
+    ),
+    (
+        "CONSOLE_BLUE_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBlue"],
+    ),
+    (
+        "CONSOLE_MAGENTA_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiMagenta"],
+    ),
+    (
+        "CONSOLE_CYAN_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiCyan"],
+    ),
+    (
+        "CONSOLE_GRAY_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiWhite"],
+    ),
+    (
+        "CONSOLE_DARKGRAY_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightBlack"],
+    ),
+    (
+        "CONSOLE_RED_BRIGHT_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightRed"],
+    ),
+    (
+        "CONSOLE_GREEN_BRIGHT_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightGreen"],
+    ),
+    (
+        "CONSOLE_YELLOW_BRIGHT_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightYellow"],
+    ),
+    (
+        "CONSOLE_BLUE_BRIGHT_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightBlue"],
+    ),
+    (
+        "CONSOLE_MAGENTA_BRIGHT_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightMagenta"],
+    ),
+    (
+        "CONSOLE_CYAN_BRIGHT_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightCyan"],
+    ),
+    (
+        "CONSOLE_WHITE_OUTPUT",
+        Part::Foreground,
+        &["terminal.ansiBrightWhite"],
+    ),
+];
+
+// (attribute key, scopes): each scope is resolved the way textmate would color it, the
+// first scope any rule covers wins. list the most specific scope first
+const SCOPE_MAPPINGS: &[(&str, &[&str])] = &[
+    ("DEFAULT_KEYWORD", &["keyword.control", "keyword"]),
+    ("DEFAULT_STRING", &["string.quoted", "string"]),
+    ("DEFAULT_NUMBER", &["constant.numeric", "constant"]),
+    ("DEFAULT_LINE_COMMENT", &["comment.line", "comment"]),
+    ("DEFAULT_BLOCK_COMMENT", &["comment.block", "comment"]),
+    (
+        "DEFAULT_DOC_COMMENT",
+        &["comment.block.documentation", "comment"],
+    ),
+    (
+        "DEFAULT_DOC_COMMENT_TAG",
+        &["storage.type.class.jsdoc", "comment.block.documentation"],
+    ),
+    ("DEFAULT_OPERATION_SIGN", &["keyword.operator"]),
+    (
+        "DEFAULT_BRACES",
+        &["punctuation.section.block", "punctuation"],
+    ),
+    (
+        "DEFAULT_BRACKETS",
+        &["punctuation.section.brackets", "punctuation"],
+    ),
+    (
+        "DEFAULT_PARENTHS",
+        &["punctuation.section.parens", "punctuation"],
+    ),
+    (
+        "DEFAULT_COMMA",
+        &["punctuation.separator.comma", "punctuation"],
+    ),
+    (
+        "DEFAULT_SEMICOLON",
+        &["punctuation.terminator.statement", "punctuation"],
+    ),
+    ("DEFAULT_DOT", &["punctuation.accessor", "punctuation"]),
+    ("DEFAULT_FUNCTION_DECLARATION", &["entity.name.function"]),
+    (
+        "DEFAULT_FUNCTION_CALL",
+        &["entity.name.function.call", "support.function"],
+    ),
+    ("DEFAULT_INSTANCE_METHOD", &["entity.name.function.member"]),
+    ("DEFAULT_STATIC_METHOD", &["entity.name.function.static"]),
+    (
+        "DEFAULT_CLASS_NAME",
+        &["entity.name.class", "entity.name.type"],
+    ),
+    (
+        "DEFAULT_INTERFACE_NAME",
+        &["entity.name.interface", "entity.name.type"],
+    ),
+    (
+        "DEFAULT_CLASS_REFERENCE",
+        &["support.class", "entity.name.type"],
+    ),
+    ("DEFAULT_IDENTIFIER", &["variable.other", "variable"]),
+    (
+        "DEFAULT_LOCAL_VARIABLE",
+        &["variable.other.readwrite", "variable"],
+    ),
+    ("DEFAULT_PARAMETER", &["variable.parameter"]),
+    (
+        "DEFAULT_INSTANCE_FIELD",
+        &["variable.member", "variable.other.member"],
+    ),
+    (
+        "DEFAULT_STATIC_FIELD",
+        &["variable.other.constant", "variable"],
+    ),
+    (
+        "DEFAULT_GLOBAL_VARIABLE",
+        &["variable.other.global", "variable"],
+    ),
+    (
+        "DEFAULT_CONSTANT",
+        &["variable.other.constant", "constant.other"],
+    ),
+    (
+        "DEFAULT_PREDEFINED_SYMBOL",
+        &["support.function.builtin", "support"],
+    ),
+    (
+        "DEFAULT_METADATA",
+        &[
+            "meta.annotation",
+            "entity.name.function.decorator",
+            "storage.type.annotation",
+        ],
+    ),
+    ("DEFAULT_LABEL", &["entity.name.label", "label"]),
+    (
+        "DEFAULT_VALID_STRING_ESCAPE",
+        &["constant.character.escape"],
+    ),
+    ("DEFAULT_INVALID_STRING_ESCAPE", &["invalid.illegal"]),
+    ("DEFAULT_TAG", &["entity.name.tag"]),
+    ("DEFAULT_ATTRIBUTE", &["entity.other.attribute-name"]),
+    ("DEFAULT_ENTITY", &["constant.character.entity", "entity"]),
+    ("DEFAULT_TEMPLATE_LANGUAGE_COLOR", &["meta.embedded"]),
+    ("BAD_CHARACTER", &["invalid.illegal", "invalid"]),
+    (
+        "MARKDOWN_HEADER_LEVEL_1",
+        &["markup.heading", "entity.name.section.markdown"],
+    ),
+    ("MARKDOWN_BOLD", &["markup.bold"]),
+    ("MARKDOWN_ITALIC", &["markup.italic"]),
+    ("MARKDOWN_LINK_DESTINATION", &["markup.underline.link"]),
+    (
+        "MARKDOWN_CODE_SPAN",
+        &["markup.inline.raw.string.markdown", "markup.inline.raw"],
+    ),
+];
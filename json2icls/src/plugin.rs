@@ -0,0 +1,347 @@
+use crate::{editor_background, escape, flatten, icls, invalid, is_light};
+use serde_json::{Map, Value};
+use std::{fmt::Write as _, fs, io, path::Path};
+
+const PLUGIN_ID: &str = "engineering.nyoom.oxocarbon";
+const PLUGIN_NAME: &str = "Oxocarbon";
+const VENDOR: &str = "Nyoom Engineering";
+const SINCE_BUILD: &str = "223";
+
+// (jetbrains ui key, vscode color keys): the first key the theme sets wins. `*.` keys are
+// the defaults every component falls back to
+const UI_KEYS: &[(&str, &[&str])] = &[
+    ("*.background", &["sideBar.background", "editor.background"]),
+    ("*.foreground", &["foreground", "editor.foreground"]),
+    (
+        "*.infoForeground",
+        &["descriptionForeground", "disabledForeground"],
+    ),
+    ("*.disabledForeground", &["disabledForeground"]),
+    ("*.selectionBackground", &["list.activeSelectionBackground"]),
+    (
+        "*.selectionForeground",
+        &["list.activeSelectionForeground", "foreground"],
+    ),
+    (
+        "*.selectionInactiveBackground",
+        &["list.inactiveSelectionBackground"],
+    ),
+    ("*.hoverBackground", &["list.hoverBackground"]),
+    ("*.borderColor", &["panel.border", "contrastBorder"]),
+    ("*.separatorColor", &["panel.border", "contrastBorder"]),
+    ("*.focusColor", &["focusBorder"]),
+    ("Borders.color", &["panel.border", "contrastBorder"]),
+    (
+        "Borders.ContrastBorderColor",
+        &["panel.border", "contrastBorder"],
+    ),
+    ("Panel.background", &["sideBar.background"]),
+    ("SidePanel.background", &["sideBar.background"]),
+    ("ToolWindow.background", &["sideBar.background"]),
+    (
+        "ToolWindow.Header.background",
+        &["sideBarSectionHeader.background", "sideBar.background"],
+    ),
+    (
+        "ToolWindow.Header.inactiveBackground",
+        &["sideBar.background"],
+    ),
+    (
+        "ToolWindow.Header.borderColor",
+        &["sideBar.border", "panel.border"],
+    ),
+    (
+        "ToolWindow.HeaderTab.selectedInactiveBackground",
+        &["list.inactiveSelectionBackground"],
+    ),
+    (
+        "ToolWindow.Button.selectedBackground",
+        &["list.activeSelectionBackground"],
+    ),
+    (
+        "ToolWindow.Button.hoverBackground",
+        &["list.hoverBackground"],
+    ),
+    ("Tree.background", &["sideBar.background"]),
+    ("Tree.foreground", &["sideBar.foreground", "foreground"]),
+    (
+        "Tree.selectionBackground",
+        &["list.activeSelectionBackground"],
+    ),
+    (
+        "Tree.selectionForeground",
+        &["list.activeSelectionForeground"],
+    ),
+    (
+        "Tree.selectionInactiveBackground",
+        &["list.inactiveSelectionBackground"],
+    ),
+    ("Tree.hoverBackground", &["list.hoverBackground"]),
+    ("List.background", &["sideBar.background"]),
+    (
+        "List.selectionBackground",
+        &["list.activeSelectionBackground"],
+    ),
+    (
+        "List.selectionForeground",
+        &["list.activeSelectionForeground"],
+    ),
+    (
+        "List.selectionInactiveBackground",
+        &["list.inactiveSelectionBackground"],
+    ),
+    ("List.hoverBackground", &["list.hoverBackground"]),
+    (
+        "EditorTabs.background",
+        &["editorGroupHeader.tabsBackground"],
+    ),
+    (
+        "EditorTabs.borderColor",
+        &["editorGroupHeader.tabsBorder", "tab.border"],
+    ),
+    (
+        "EditorTabs.underlinedTabBackground",
+        &["tab.activeBackground"],
+    ),
+    (
+        "EditorTabs.underlinedTabForeground",
+        &["tab.activeForeground"],
+    ),
+    (
+        "EditorTabs.underlineColor",
+        &["tab.activeBorderTop", "tab.activeBorder"],
+    ),
+    (
+        "EditorTabs.inactiveUnderlineColor",
+        &["tab.unfocusedActiveBorderTop", "tab.unfocusedActiveBorder"],
+    ),
+    ("EditorTabs.hoverBackground", &["tab.hoverBackground"]),
+    (
+        "EditorTabs.inactiveColoredFileBackground",
+        &["tab.inactiveBackground"],
+    ),
+    ("MainToolbar.background", &["titleBar.activeBackground"]),
+    (
+        "MainToolbar.inactiveBackground",
+        &["titleBar.inactiveBackground"],
+    ),
+    ("MainToolbar.foreground", &["titleBar.activeForeground"]),
+    ("TitlePane.background", &["titleBar.activeBackground"]),
+    (
+        "TitlePane.inactiveBackground",
+        &["titleBar.inactiveBackground"],
+    ),
+    ("TitlePane.infoForeground", &["titleBar.activeForeground"]),
+    (
+        "TitlePane.inactiveInfoForeground",
+        &["titleBar.inactiveForeground"],
+    ),
+    ("StatusBar.background", &["statusBar.background"]),
+    ("StatusBar.borderColor", &["statusBar.border"]),
+    (
+        "StatusBar.Breadcrumbs.foreground",
+        &["statusBar.foreground"],
+    ),
+    (
+        "StatusBar.Widget.hoverBackground",
+        &["statusBarItem.hoverBackground"],
+    ),
+    ("Button.default.startBackground", &["button.background"]),
+    ("Button.default.endBackground", &["button.background"]),
+    ("Button.default.foreground", &["button.foreground"]),
+    (
+        "Button.default.startBorderColor",
+        &["button.border", "button.background"],
+    ),
+    (
+        "Button.default.endBorderColor",
+        &["button.border", "button.background"],
+    ),
+    (
+        "Button.startBackground",
+        &["button.secondaryBackground", "button.background"],
+    ),
+    (
+        "Button.endBackground",
+        &["button.secondaryBackground", "button.background"],
+    ),
+    (
+        "Button.foreground",
+        &["button.secondaryForeground", "button.foreground"],
+    ),
+    ("TextField.background", &["input.background"]),
+    ("TextField.foreground", &["input.foreground"]),
+    ("TextArea.background", &["input.background"]),
+    ("TextArea.foreground", &["input.foreground"]),
+    ("ComboBox.background", &["dropdown.background"]),
+    ("ComboBox.nonEditableBackground", &["dropdown.background"]),
+    ("ComboBox.foreground", &["dropdown.foreground"]),
+    (
+        "Component.borderColor",
+        &["input.border", "dropdown.border", "panel.border"],
+    ),
+    ("Component.focusedBorderColor", &["focusBorder"]),
+    ("Component.focusColor", &["focusBorder"]),
+    ("CheckBox.background", &["checkbox.background"]),
+    ("Link.activeForeground", &["textLink.foreground"]),
+    ("Link.hoverForeground", &["textLink.activeForeground"]),
+    ("Link.pressedForeground", &["textLink.activeForeground"]),
+    (
+        "Popup.background",
+        &["editorWidget.background", "menu.background"],
+    ),
+    (
+        "Popup.borderColor",
+        &["editorWidget.border", "widget.border"],
+    ),
+    ("PopupMenu.background", &["menu.background"]),
+    ("PopupMenu.foreground", &["menu.foreground"]),
+    (
+        "PopupMenu.selectionBackground",
+        &["menu.selectionBackground"],
+    ),
+    ("Menu.background", &["menu.background"]),
+    ("Menu.foreground", &["menu.foreground"]),
+    ("Menu.separatorColor", &["menu.separatorBackground"]),
+    (
+        "MenuItem.selectionBackground",
+        &["menu.selectionBackground"],
+    ),
+    (
+        "MenuItem.selectionForeground",
+        &["menu.selectionForeground"],
+    ),
+    ("ToolTip.background", &["editorHoverWidget.background"]),
+    ("ToolTip.foreground", &["editorHoverWidget.foreground"]),
+    ("ToolTip.borderColor", &["editorHoverWidget.border"]),
+    ("Notification.background", &["notifications.background"]),
+    (
+        "Notification.borderColor",
+        &["notifications.border", "widget.border"],
+    ),
+    ("ProgressBar.progressColor", &["progressBar.background"]),
+    (
+        "ProgressBar.indeterminateStartColor",
+        &["progressBar.background"],
+    ),
+    (
+        "ProgressBar.indeterminateEndColor",
+        &["progressBar.background"],
+    ),
+    ("ScrollBar.thumbColor", &["scrollbarSlider.background"]),
+    (
+        "ScrollBar.hoverThumbColor",
+        &["scrollbarSlider.hoverBackground"],
+    ),
+    ("ScrollBar.Mac.thumbColor", &["scrollbarSlider.background"]),
+    (
+        "ScrollBar.Mac.hoverThumbColor",
+        &["scrollbarSlider.hoverBackground"],
+    ),
+    (
+        "SearchMatch.startBackground",
+        &["editor.findMatchBackground"],
+    ),
+    ("SearchMatch.endBackground", &["editor.findMatchBackground"]),
+    (
+        "Counter.background",
+        &["badge.background", "activityBarBadge.background"],
+    ),
+    (
+        "Counter.foreground",
+        &["badge.foreground", "activityBarBadge.foreground"],
+    ),
+    (
+        "ActionButton.hoverBackground",
+        &["toolbar.hoverBackground", "list.hoverBackground"],
+    ),
+    (
+        "Separator.separatorColor",
+        &["panel.border", "contrastBorder"],
+    ),
+];
+
+// writes an unpacked theme plugin: `META-INF/plugin.xml` plus a `.theme.json` and editor
+// scheme per input under `themes/`. zipping `dir` as a jar gives an installable plugin
+pub(crate) fn write(dir: &Path, inputs: &[String], version: &str) -> io::Result<()> {
+    let themes_dir = dir.join("themes");
+    fs::create_dir_all(&themes_dir)?;
+    fs::create_dir_all(dir.join("META-INF"))?;
+
+    let mut providers = String::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| invalid("Invalid input path"))?;
+        let theme: Value = serde_json::from_reader(fs::File::open(path)?)?;
+
+        fs::write(themes_dir.join(format!("{stem}.xml")), icls(&theme)?)?;
+        let ui_theme = ui_theme(&theme, &format!("/themes/{stem}.xml"))?;
+        let mut json = serde_json::to_string_pretty(&ui_theme)?;
+        json.push('\n');
+        fs::write(themes_dir.join(format!("{stem}.theme.json")), json)?;
+
+        let _ = writeln!(
+            providers,
+            "    <themeProvider id=\"{PLUGIN_ID}.{stem}\" path=\"/themes/{stem}.theme.json\" />"
+        );
+    }
+
+    let plugin_xml = format!(
+        "<idea-plugin>
+  <id>{PLUGIN_ID}</id>
+  <name>{PLUGIN_NAME}</name>
+  <version>{}</version>
+  <vendor>{VENDOR}</vendor>
+  <description>Oxocarbon UI themes and editor color schemes</description>
+  <idea-version since-build=\"{SINCE_BUILD}\" />
+  <depends>com.intellij.modules.platform</depends>
+  <extensions defaultExtensionNs=\"com.intellij\">
+{providers}  </extensions>
+</idea-plugin>
+",
+        escape(version)
+    );
+    fs::write(dir.join("META-INF/plugin.xml"), plugin_xml)
+}
+
+fn ui_theme(theme: &Value, editor_scheme: &str) -> io::Result<Value> {
+    let name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let background = editor_background(colors)?;
+
+    let mut defaults = Map::new();
+    let mut ui = Map::new();
+    for (key, keys) in UI_KEYS {
+        let Some(color) = keys
+            .iter()
+            .find_map(|k| colors.get(*k).and_then(|v| v.as_str()))
+            .and_then(|hex| flatten(hex, background))
+        else {
+            continue;
+        };
+        let color = Value::String(format!("#{color}"));
+        match key.strip_prefix("*.") {
+            Some(key) => defaults.insert(key.to_string(), color),
+            None => ui.insert((*key).to_string(), color),
+        };
+    }
+    ui.insert("*".to_string(), Value::Object(defaults));
+
+    let mut out = Map::new();
+    out.insert("name".to_string(), Value::String(name.to_string()));
+    out.insert("dark".to_string(), Value::Bool(!is_light(background)));
+    out.insert("author".to_string(), Value::String(VENDOR.to_string()));
+    out.insert(
+        "editorScheme".to_string(),
+        Value::String(editor_scheme.to_string()),
+    );
+    out.insert("ui".to_string(), Value::Object(ui));
+    Ok(Value::Object(out))
+}
@@ -0,0 +1,676 @@
+use oxocarbon_utils::{composite_over, luminance_from_u8, parse_hex_rgba_u8 as parse_hex};
+use serde_json::{Map, Value};
+use std::{env, fmt::Write as _, fs, io, path::Path};
+
+/// the part of a highlight group a vscode color sets
+enum Part {
+    Fg,
+    Bg,
+    Sp,
+}
+
+// (group, part, vscode color keys): the first key the theme sets wins
+const UI_GROUPS: &[(&str, Part, &[&str])] = &[
+    ("Normal", Part::Fg, &["editor.foreground", "foreground"]),
+    ("Normal", Part::Bg, &["editor.background"]),
+    (
+        "NormalFloat",
+        Part::Fg,
+        &["editorWidget.foreground", "editor.foreground"],
+    ),
+    ("NormalFloat", Part::Bg, &["editorWidget.background"]),
+    (
+        "FloatBorder",
+        Part::Fg,
+        &["editorWidget.border", "widget.border"],
+    ),
+    ("FloatBorder", Part::Bg, &["editorWidget.background"]),
+    ("FloatTitle", Part::Fg, &["peekViewTitleLabel.foreground"]),
+    (
+        "Cursor",
+        Part::Fg,
+        &["editorCursor.background", "editor.background"],
+    ),
+    ("Cursor", Part::Bg, &["editorCursor.foreground"]),
+    (
+        "CursorLine",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    (
+        "CursorColumn",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    ("ColorColumn", Part::Bg, &["editorRuler.foreground"]),
+    ("LineNr", Part::Fg, &["editorLineNumber.foreground"]),
+    (
+        "CursorLineNr",
+        Part::Fg,
+        &["editorLineNumber.activeForeground", "editor.foreground"],
+    ),
+    (
+        "SignColumn",
+        Part::Bg,
+        &["editorGutter.background", "editor.background"],
+    ),
+    (
+        "FoldColumn",
+        Part::Fg,
+        &["editorGutter.foldingControlForeground"],
+    ),
+    (
+        "Folded",
+        Part::Bg,
+        &[
+            "editor.foldBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    ("Visual", Part::Bg, &["editor.selectionBackground"]),
+    (
+        "VisualNOS",
+        Part::Bg,
+        &["editor.inactiveSelectionBackground"],
+    ),
+    ("Search", Part::Fg, &["editor.findMatchHighlightForeground"]),
+    ("Search", Part::Bg, &["editor.findMatchHighlightBackground"]),
+    ("CurSearch", Part::Fg, &["editor.findMatchForeground"]),
+    ("CurSearch", Part::Bg, &["editor.findMatchBackground"]),
+    ("IncSearch", Part::Fg, &["editor.findMatchForeground"]),
+    ("IncSearch", Part::Bg, &["editor.findMatchBackground"]),
+    // editorBracketMatch.background is transparent in oxocarbon
+    ("MatchParen", Part::Bg, &["editor.selectionBackground"]),
+    ("Pmenu", Part::Fg, &["editorSuggestWidget.foreground"]),
+    ("Pmenu", Part::Bg, &["editorSuggestWidget.background"]),
+    (
+        "PmenuSel",
+        Part::Fg,
+        &["editorSuggestWidget.selectedForeground"],
+    ),
+    (
+        "PmenuSel",
+        Part::Bg,
+        &["editorSuggestWidget.selectedBackground"],
+    ),
+    (
+        "PmenuMatch",
+        Part::Fg,
+        &["editorSuggestWidget.highlightForeground"],
+    ),
+    (
+        "PmenuMatchSel",
+        Part::Fg,
+        &["editorSuggestWidget.focusHighlightForeground"],
+    ),
+    ("PmenuSbar", Part::Bg, &["editorSuggestWidget.background"]),
+    (
+        "PmenuThumb",
+        Part::Bg,
+        &[
+            "scrollbarSlider.activeBackground",
+            "minimapSlider.activeBackground",
+        ],
+    ),
+    ("StatusLine", Part::Fg, &["statusBar.foreground"]),
+    ("StatusLine", Part::Bg, &["statusBar.background"]),
+    ("StatusLineNC", Part::Fg, &["tab.inactiveForeground"]),
+    ("StatusLineNC", Part::Bg, &["statusBar.background"]),
+    ("TabLine", Part::Fg, &["tab.inactiveForeground"]),
+    ("TabLine", Part::Bg, &["tab.inactiveBackground"]),
+    ("TabLineSel", Part::Fg, &["tab.activeForeground"]),
+    ("TabLineSel", Part::Bg, &["tab.activeBackground"]),
+    (
+        "TabLineFill",
+        Part::Bg,
+        &["editorGroupHeader.tabsBackground"],
+    ),
+    (
+        "WinSeparator",
+        Part::Fg,
+        &["editorGroup.border", "panel.border"],
+    ),
+    (
+        "NonText",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    (
+        "Whitespace",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    (
+        "SpecialKey",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    ("Directory", Part::Fg, &["textLink.foreground"]),
+    (
+        "ErrorMsg",
+        Part::Fg,
+        &["errorForeground", "editorError.foreground"],
+    ),
+    ("WarningMsg", Part::Fg, &["editorWarning.foreground"]),
+    ("MoreMsg", Part::Fg, &["textLink.foreground"]),
+    ("Question", Part::Fg, &["textLink.foreground"]),
+    ("WildMenu", Part::Bg, &["list.activeSelectionBackground"]),
+    (
+        "QuickFixLine",
+        Part::Bg,
+        &["list.activeSelectionBackground"],
+    ),
+    (
+        "LspReferenceText",
+        Part::Bg,
+        &["editor.wordHighlightBackground"],
+    ),
+    (
+        "LspReferenceRead",
+        Part::Bg,
+        &["editor.wordHighlightBackground"],
+    ),
+    (
+        "LspReferenceWrite",
+        Part::Bg,
+        &["editor.wordHighlightStrongBackground"],
+    ),
+    ("LspInlayHint", Part::Fg, &["editorInlayHint.foreground"]),
+    ("LspInlayHint", Part::Bg, &["editorInlayHint.background"]),
+    ("DiffAdd", Part::Bg, &["diffEditor.insertedLineBackground"]),
+    (
+        "DiffDelete",
+        Part::Bg,
+        &["diffEditor.removedLineBackground"],
+    ),
+    ("DiffChange", Part::Bg, &["merge.incomingContentBackground"]),
+    ("DiffText", Part::Bg, &["merge.incomingHeaderBackground"]),
+    ("Added", Part::Fg, &["editorGutter.addedBackground"]),
+    ("Changed", Part::Fg, &["editorGutter.modifiedBackground"]),
+    ("Removed", Part::Fg, &["editorGutter.deletedBackground"]),
+    ("SpellBad", Part::Sp, &["editorError.foreground"]),
+    ("SpellCap", Part::Sp, &["editorWarning.foreground"]),
+    ("SpellLocal", Part::Sp, &["editorInfo.foreground"]),
+    ("SpellRare", Part::Sp, &["editorHint.foreground"]),
+];
+
+// (severity, vscode color keys): each sets the Diagnostic{,Sign,VirtualText,Underline} groups
+const DIAGNOSTICS: &[(&str, &[&str])] = &[
+    ("Error", &["editorError.foreground", "errorForeground"]),
+    ("Warn", &["editorWarning.foreground"]),
+    ("Info", &["editorInfo.foreground"]),
+    ("Hint", &["editorHint.foreground", "editorInfo.foreground"]),
+    ("Ok", &["editorGutter.addedBackground"]),
+];
+
+// (tree-sitter capture, scopes): each scope is resolved the way textmate would color it, the
+// first scope any rule covers wins. list the most specific scope first
+const CAPTURES: &[(&str, &[&str])] = &[
+    ("@comment", &["comment"]),
+    (
+        "@comment.documentation",
+        &["comment.block.documentation", "comment"],
+    ),
+    ("@keyword", &["keyword"]),
+    (
+        "@keyword.function",
+        &["keyword.other.fn", "storage.type.function", "keyword"],
+    ),
+    (
+        "@keyword.return",
+        &["keyword.control.return", "keyword.control"],
+    ),
+    (
+        "@keyword.conditional",
+        &["keyword.control.conditional", "keyword.control"],
+    ),
+    (
+        "@keyword.repeat",
+        &["keyword.control.loop", "keyword.control"],
+    ),
+    (
+        "@keyword.exception",
+        &["keyword.control.exception", "keyword.control"],
+    ),
+    (
+        "@keyword.import",
+        &["keyword.control.import", "storage.modifier.import"],
+    ),
+    (
+        "@keyword.operator",
+        &["keyword.operator.word", "keyword.operator"],
+    ),
+    ("@keyword.modifier", &["storage.modifier"]),
+    ("@keyword.type", &["storage.type"]),
+    (
+        "@keyword.directive",
+        &["keyword.control.directive", "preproc"],
+    ),
+    ("@operator", &["keyword.operator"]),
+    ("@string", &["string"]),
+    ("@string.escape", &["constant.character.escape", "string"]),
+    ("@string.regexp", &["string.regexp", "string"]),
+    ("@string.special.url", &["markup.underline.link"]),
+    ("@character", &["constant.character", "string"]),
+    ("@number", &["constant.numeric", "constant"]),
+    (
+        "@number.float",
+        &["constant.numeric.float", "constant.numeric"],
+    ),
+    (
+        "@boolean",
+        &["constant.language.boolean", "constant.language"],
+    ),
+    ("@constant", &["constant.other", "constant"]),
+    ("@constant.builtin", &["constant.language", "constant"]),
+    ("@function", &["entity.name.function"]),
+    (
+        "@function.call",
+        &["entity.name.function.call", "entity.name.function"],
+    ),
+    ("@function.builtin", &["support.function"]),
+    (
+        "@function.method",
+        &["entity.name.function.member", "entity.name.function"],
+    ),
+    (
+        "@function.macro",
+        &["entity.name.function.macro", "entity.name.function"],
+    ),
+    (
+        "@constructor",
+        &["entity.name.function.constructor", "support.class"],
+    ),
+    ("@type", &["entity.name.type", "entity"]),
+    ("@type.builtin", &["support.type", "storage.type"]),
+    ("@type.definition", &["entity.name.type", "entity"]),
+    (
+        "@module",
+        &["entity.name.namespace", "entity.name.type.module"],
+    ),
+    ("@variable", &["variable"]),
+    ("@variable.builtin", &["variable.language", "variable"]),
+    ("@variable.parameter", &["variable.parameter"]),
+    (
+        "@variable.member",
+        &["variable.member", "variable.other.property"],
+    ),
+    ("@property", &["variable.member", "variable.other.property"]),
+    ("@attribute", &["entity.other.attribute-name"]),
+    ("@label", &["entity.name.label", "label"]),
+    ("@tag", &["entity.name.tag"]),
+    ("@tag.attribute", &["entity.other.attribute-name"]),
+    ("@tag.delimiter", &["punctuation.definition.tag"]),
+    (
+        "@punctuation.delimiter",
+        &["punctuation.separator", "punctuation"],
+    ),
+    (
+        "@punctuation.bracket",
+        &["punctuation.section", "punctuation"],
+    ),
+    (
+        "@punctuation.special",
+        &["punctuation.section.embedded", "punctuation.definition"],
+    ),
+    (
+        "@markup.heading",
+        &["markup.heading", "entity.name.section.markdown"],
+    ),
+    ("@markup.strong", &["markup.bold"]),
+    ("@markup.italic", &["markup.italic"]),
+    ("@markup.link.url", &["markup.underline.link"]),
+    (
+        "@markup.raw",
+        &["markup.inline.raw.string.markdown", "markup.raw"],
+    ),
+    (
+        "@markup.list",
+        &["punctuation.definition.list", "markup.list"],
+    ),
+    ("@markup.quote", &["markup.quote"]),
+    ("@diff.plus", &["markup.inserted"]),
+    ("@diff.minus", &["markup.deleted"]),
+    ("@diff.delta", &["markup.changed"]),
+];
+
+// (group, target): legacy syntax and lsp semantic token groups linked onto the captures.
+// a link is only written when its target is
+const LINKS: &[(&str, &str)] = &[
+    ("Comment", "@comment"),
+    ("String", "@string"),
+    ("Character", "@character"),
+    ("Number", "@number"),
+    ("Float", "@number.float"),
+    ("Boolean", "@boolean"),
+    ("Constant", "@constant"),
+    ("Identifier", "@variable"),
+    ("Function", "@function"),
+    ("Statement", "@keyword"),
+    ("Keyword", "@keyword"),
+    ("Conditional", "@keyword.conditional"),
+    ("Repeat", "@keyword.repeat"),
+    ("Exception", "@keyword.exception"),
+    ("Label", "@label"),
+    ("Operator", "@operator"),
+    ("PreProc", "@keyword.directive"),
+    ("Include", "@keyword.import"),
+    ("Type", "@type"),
+    ("StorageClass", "@keyword.modifier"),
+    ("Structure", "@type"),
+    ("Special", "@punctuation.special"),
+    ("SpecialChar", "@string.escape"),
+    ("Delimiter", "@punctuation.delimiter"),
+    ("Tag", "@tag"),
+    ("Title", "@markup.heading"),
+    ("Underlined", "@markup.link.url"),
+    ("@lsp.type.class", "@type"),
+    ("@lsp.type.enum", "@type"),
+    ("@lsp.type.interface", "@type"),
+    ("@lsp.type.struct", "@type"),
+    ("@lsp.type.type", "@type"),
+    ("@lsp.type.typeParameter", "@type.definition"),
+    ("@lsp.type.namespace", "@module"),
+    ("@lsp.type.parameter", "@variable.parameter"),
+    ("@lsp.type.variable", "@variable"),
+    ("@lsp.type.property", "@property"),
+    ("@lsp.type.enumMember", "@constant"),
+    ("@lsp.type.function", "@function"),
+    ("@lsp.type.method", "@function.method"),
+    ("@lsp.type.macro", "@function.macro"),
+    ("@lsp.type.decorator", "@attribute"),
+    ("@lsp.type.keyword", "@keyword"),
+    ("@lsp.type.string", "@string"),
+    ("@lsp.type.number", "@number"),
+    ("@lsp.type.comment", "@comment"),
+    ("@lsp.type.operator", "@operator"),
+    ("@lsp.typemod.function.defaultLibrary", "@function.builtin"),
+    ("@lsp.typemod.variable.defaultLibrary", "@variable.builtin"),
+];
+
+// terminal_color_0..15, in ansi order
+const TERMINAL_COLORS: [&str; 16] = [
+    "terminal.ansiBlack",
+    "terminal.ansiRed",
+    "terminal.ansiGreen",
+    "terminal.ansiYellow",
+    "terminal.ansiBlue",
+    "terminal.ansiMagenta",
+    "terminal.ansiCyan",
+    "terminal.ansiWhite",
+    "terminal.ansiBrightBlack",
+    "terminal.ansiBrightRed",
+    "terminal.ansiBrightGreen",
+    "terminal.ansiBrightYellow",
+    "terminal.ansiBrightBlue",
+    "terminal.ansiBrightMagenta",
+    "terminal.ansiBrightCyan",
+    "terminal.ansiBrightWhite",
+];
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let name = match args.iter().position(|a| a == "--name") {
+        Some(i) if i + 1 < args.len() => {
+            let name = args.remove(i + 1);
+            args.remove(i);
+            Some(name)
+        }
+        Some(_) => usage(),
+        None => None,
+    };
+    if args.len() != 2 {
+        usage();
+    }
+
+    // the colorscheme name has to match the file neovim finds it in
+    let name = name
+        .or_else(|| {
+            (args[1] != "-")
+                .then(|| Path::new(&args[1]).file_stem()?.to_str().map(String::from))
+                .flatten()
+        })
+        .unwrap_or_else(|| "oxocarbon".to_string());
+
+    let reader: Box<dyn io::Read> = match args[0].as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+
+    let theme: Value = serde_json::from_reader(reader)?;
+    let out = colorscheme(&theme, &name)?;
+
+    match args[1].as_str() {
+        "-" => io::Write::write_all(&mut io::stdout().lock(), out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: json2nvim [--name <colorscheme>] <input.json|-> <output.lua|->");
+    std::process::exit(2);
+}
+
+#[derive(Default)]
+struct Highlight {
+    fg: Option<String>,
+    bg: Option<String>,
+    sp: Option<String>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    undercurl: bool,
+    strikethrough: bool,
+    link: Option<&'static str>,
+}
+
+impl Highlight {
+    fn set(&mut self, part: &Part, color: String) {
+        match part {
+            Part::Fg => self.fg = Some(color),
+            Part::Bg => self.bg = Some(color),
+            Part::Sp => {
+                self.sp = Some(color);
+                self.undercurl = true;
+            }
+        }
+    }
+
+    fn set_font_style(&mut self, font_style: &str) {
+        for style in font_style.split_whitespace() {
+            match style.to_ascii_lowercase().as_str() {
+                "bold" => self.bold = true,
+                "italic" => self.italic = true,
+                "underline" => self.underline = true,
+                "strikethrough" => self.strikethrough = true,
+                _ => {}
+            }
+        }
+    }
+
+    fn to_lua(&self) -> String {
+        if let Some(link) = self.link {
+            return format!("{{ link = \"{link}\" }}");
+        }
+        let mut fields = Vec::new();
+        for (key, color) in [("fg", &self.fg), ("bg", &self.bg), ("sp", &self.sp)] {
+            if let Some(color) = color {
+                fields.push(format!("{key} = \"#{color}\""));
+            }
+        }
+        for (key, set) in [
+            ("bold", self.bold),
+            ("italic", self.italic),
+            ("underline", self.underline),
+            ("undercurl", self.undercurl),
+            ("strikethrough", self.strikethrough),
+        ] {
+            if set {
+                fields.push(format!("{key} = true"));
+            }
+        }
+        format!("{{ {} }}", fields.join(", "))
+    }
+}
+
+fn colorscheme(theme: &Value, name: &str) -> io::Result<String> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let theme_name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let tokens = theme["tokenColors"]
+        .as_array()
+        .ok_or_else(|| invalid("Missing tokenColors"))?;
+
+    let background = colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex)
+        .map(|(rgb, _)| rgb)
+        .ok_or_else(|| invalid("Invalid color: editor.background"))?;
+    // neovim has no alpha, translucent colors are flattened onto the editor background
+    let flatten = |hex: &str| {
+        parse_hex(hex).map(|(rgb, alpha)| {
+            let [r, g, b] = composite_over(rgb, alpha, background);
+            format!("{r:02x}{g:02x}{b:02x}")
+        })
+    };
+    let get_color = |keys: &[&str]| get_color(colors, keys).and_then(flatten);
+
+    // groups in the order they are written
+    let mut groups: Vec<(String, Highlight)> = Vec::new();
+    let group = |groups: &mut Vec<(String, Highlight)>, name: &str| -> usize {
+        groups
+            .iter()
+            .position(|(n, _)| n == name)
+            .unwrap_or_else(|| {
+                groups.push((name.to_string(), Highlight::default()));
+                groups.len() - 1
+            })
+    };
+
+    for (name, part, keys) in UI_GROUPS {
+        if let Some(color) = get_color(keys) {
+            let i = group(&mut groups, name);
+            groups[i].1.set(part, color);
+        }
+    }
+
+    for (severity, keys) in DIAGNOSTICS {
+        let Some(color) = get_color(keys) else {
+            continue;
+        };
+        for prefix in ["Diagnostic", "DiagnosticSign", "DiagnosticVirtualText"] {
+            let i = group(&mut groups, &format!("{prefix}{severity}"));
+            groups[i].1.set(&Part::Fg, color.clone());
+        }
+        let i = group(&mut groups, &format!("DiagnosticUnderline{severity}"));
+        groups[i].1.set(&Part::Sp, color);
+    }
+
+    for (capture, scopes) in CAPTURES {
+        // the font style comes from the same scope as the color
+        let Some((scope, fg)) = scopes
+            .iter()
+            .find_map(|s| Some((s, flatten(resolve(tokens, s, "foreground")?)?)))
+        else {
+            continue;
+        };
+        let i = group(&mut groups, capture);
+        groups[i].1.fg = Some(fg);
+        if let Some(font_style) = resolve(tokens, scope, "fontStyle") {
+            groups[i].1.set_font_style(font_style);
+        }
+    }
+
+    for (name, target) in LINKS {
+        if groups.iter().any(|(n, _)| n == target) {
+            let i = group(&mut groups, name);
+            groups[i].1.link = Some(target);
+        }
+    }
+
+    let [r, g, b] = background;
+    let mode = if luminance_from_u8(r, g, b) > 0.5 {
+        "light"
+    } else {
+        "dark"
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "-- {theme_name}, generated by json2nvim\n");
+    out.push_str("vim.cmd.highlight(\"clear\")\n");
+    let _ = writeln!(out, "vim.g.colors_name = \"{name}\"");
+    let _ = writeln!(out, "vim.o.background = \"{mode}\"\n");
+    out.push_str("local groups = {\n");
+    for (name, highlight) in &groups {
+        let is_ident = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_ident {
+            let _ = writeln!(out, "  {name} = {},", highlight.to_lua());
+        } else {
+            let _ = writeln!(out, "  [\"{name}\"] = {},", highlight.to_lua());
+        }
+    }
+    out.push_str("}\n\nfor group, spec in pairs(groups) do\n");
+    out.push_str("  vim.api.nvim_set_hl(0, group, spec)\nend\n\n");
+    for (i, key) in TERMINAL_COLORS.iter().enumerate() {
+        if let Some(color) = get_color(&[key]) {
+            let _ = writeln!(out, "vim.g.terminal_color_{i} = \"#{color}\"");
+        }
+    }
+    Ok(out)
+}
+
+fn get_color<'a>(colors: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| colors.get(*k).and_then(|v| v.as_str()))
+}
+
+// the `setting` textmate would give `scope`: longest matching selector among rules that
+// set it, later rules winning ties
+fn resolve<'a>(tokens: &'a [Value], scope: &str, setting: &str) -> Option<&'a str> {
+    let scope_matches = |pat: &str| {
+        scope.starts_with(pat)
+            && (scope.len() == pat.len() || scope.as_bytes().get(pat.len()) == Some(&b'.'))
+    };
+    tokens
+        .iter()
+        .filter_map(|item| {
+            let value = item.get("settings")?.get(setting)?.as_str()?;
+            let len = scopes(item)
+                .into_iter()
+                .filter(|s| scope_matches(s))
+                .map(str::len)
+                .max()?;
+            Some((len, value))
+        })
+        .fold(
+            None,
+            |best: Option<(usize, &str)>, (len, value)| match best {
+                Some((best_len, _)) if best_len > len => best,
+                _ => Some((len, value)),
+            },
+        )
+        .map(|(_, value)| value)
+}
+
+fn scopes(item: &Value) -> Vec<&str> {
+    match item.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::Array(arr)) => arr.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,425 @@
+use oxocarbon_utils::{composite_over, parse_hex_rgba_u8 as parse_hex};
+use plist::to_writer_xml;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, env, fmt::Write as _, fs, io};
+
+const AUTHOR: &str = "Nyoom Engineering";
+
+// ansi color keys, normal then bright
+const ANSI: [&str; 16] = [
+    "terminal.ansiBlack",
+    "terminal.ansiRed",
+    "terminal.ansiGreen",
+    "terminal.ansiYellow",
+    "terminal.ansiBlue",
+    "terminal.ansiMagenta",
+    "terminal.ansiCyan",
+    "terminal.ansiWhite",
+    "terminal.ansiBrightBlack",
+    "terminal.ansiBrightRed",
+    "terminal.ansiBrightGreen",
+    "terminal.ansiBrightYellow",
+    "terminal.ansiBrightBlue",
+    "terminal.ansiBrightMagenta",
+    "terminal.ansiBrightCyan",
+    "terminal.ansiBrightWhite",
+];
+
+// alacritty's names for the eight colors of each half
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+#[derive(Clone, Copy)]
+enum Format {
+    Alacritty,
+    Kitty,
+    Ghostty,
+    WezTerm,
+    Foot,
+    Iterm2,
+    WindowsTerminal,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "alacritty" => Some(Self::Alacritty),
+            "kitty" => Some(Self::Kitty),
+            "ghostty" => Some(Self::Ghostty),
+            "wezterm" => Some(Self::WezTerm),
+            "foot" => Some(Self::Foot),
+            "iterm2" => Some(Self::Iterm2),
+            "windows-terminal" => Some(Self::WindowsTerminal),
+            _ => None,
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let format = match args.iter().position(|a| a == "--format") {
+        Some(i) if i + 1 < args.len() => {
+            let format = Format::parse(&args.remove(i + 1)).unwrap_or_else(|| usage());
+            args.remove(i);
+            format
+        }
+        _ => usage(),
+    };
+    if args.len() != 2 {
+        usage();
+    }
+
+    let reader: Box<dyn io::Read> = match args[0].as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+
+    let theme: Value = serde_json::from_reader(reader)?;
+    let palette = Palette::from_theme(&theme)?;
+    let out = match format {
+        Format::Alacritty => alacritty(&palette),
+        Format::Kitty => kitty(&palette),
+        Format::Ghostty => ghostty(&palette),
+        Format::WezTerm => wezterm(&palette),
+        Format::Foot => foot(&palette),
+        Format::Iterm2 => iterm2(&palette)?,
+        Format::WindowsTerminal => windows_terminal(&palette)?,
+    };
+
+    match args[1].as_str() {
+        "-" => io::Write::write_all(&mut io::stdout().lock(), out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: json2term --format <alacritty|kitty|ghostty|wezterm|foot|iterm2|windows-terminal> <input.json|-> <output|->"
+    );
+    std::process::exit(2);
+}
+
+/// the colors every terminal format shares, opaque
+struct Palette {
+    name: String,
+    foreground: [u8; 3],
+    background: [u8; 3],
+    cursor: [u8; 3],
+    cursor_text: [u8; 3],
+    selection_background: [u8; 3],
+    /// terminals keep the cell's own color when a theme sets none
+    selection_foreground: Option<[u8; 3]>,
+    ansi: [[u8; 3]; 16],
+}
+
+impl Palette {
+    fn from_theme(theme: &Value) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let name = theme["name"]
+            .as_str()
+            .ok_or_else(|| invalid("Missing name".to_string()))?;
+        let colors = theme["colors"]
+            .as_object()
+            .ok_or_else(|| invalid("Missing colors".to_string()))?;
+
+        let background = get_color(colors, &["terminal.background", "editor.background"])
+            .and_then(parse_hex)
+            .map(|(rgb, _)| rgb)
+            .ok_or_else(|| invalid("Invalid color: terminal.background".to_string()))?;
+        // terminal configs take opaque colors, translucent ones are flattened onto the
+        // terminal background
+        let color = |keys: &[&str]| {
+            get_color(colors, keys)
+                .and_then(parse_hex)
+                .map(|(rgb, alpha)| composite_over(rgb, alpha, background))
+        };
+        let required = |keys: &[&str]| {
+            color(keys).ok_or_else(|| invalid(format!("Missing color: {}", keys[0])))
+        };
+
+        let mut ansi = [[0; 3]; 16];
+        for (slot, key) in ansi.iter_mut().zip(ANSI) {
+            *slot = required(&[key])?;
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            foreground: required(&["terminal.foreground", "editor.foreground"])?,
+            background,
+            cursor: required(&["terminalCursor.foreground", "editorCursor.foreground"])?,
+            cursor_text: color(&["terminalCursor.background"]).unwrap_or(background),
+            selection_background: required(&[
+                "terminal.selectionBackground",
+                "editor.selectionBackground",
+            ])?,
+            selection_foreground: color(&["terminal.selectionForeground"]),
+            ansi,
+        })
+    }
+}
+
+fn get_color<'a>(colors: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| colors.get(*k).and_then(|v| v.as_str()))
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+// toml basic string
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn alacritty(p: &Palette) -> String {
+    let mut out = format!("# {}, generated by json2term\n\n", p.name);
+    let _ = writeln!(
+        out,
+        "[colors.primary]\nbackground = \"{}\"\nforeground = \"{}\"\n",
+        hex(p.background),
+        hex(p.foreground)
+    );
+    let _ = writeln!(
+        out,
+        "[colors.cursor]\ntext = \"{}\"\ncursor = \"{}\"\n",
+        hex(p.cursor_text),
+        hex(p.cursor)
+    );
+    let selection_text = p
+        .selection_foreground
+        .map_or_else(|| "CellForeground".to_string(), hex);
+    let _ = writeln!(
+        out,
+        "[colors.selection]\ntext = \"{selection_text}\"\nbackground = \"{}\"",
+        hex(p.selection_background)
+    );
+    for (table, colors) in ["normal", "bright"].iter().zip(p.ansi.chunks(8)) {
+        let _ = writeln!(out, "\n[colors.{table}]");
+        for (name, rgb) in ANSI_NAMES.iter().zip(colors) {
+            let _ = writeln!(out, "{name} = \"{}\"", hex(*rgb));
+        }
+    }
+    out
+}
+
+fn kitty(p: &Palette) -> String {
+    // the `##` header is what kitty's theme kitten lists
+    let mut out = format!(
+        "## name: {}\n## author: {AUTHOR}\n## license: MIT\n## blurb: generated by json2term\n\n",
+        p.name
+    );
+    let _ = writeln!(out, "foreground {}", hex(p.foreground));
+    let _ = writeln!(out, "background {}", hex(p.background));
+    let _ = writeln!(
+        out,
+        "selection_foreground {}",
+        p.selection_foreground
+            .map_or_else(|| "none".to_string(), hex)
+    );
+    let _ = writeln!(out, "selection_background {}", hex(p.selection_background));
+    let _ = writeln!(out, "cursor {}", hex(p.cursor));
+    let _ = writeln!(out, "cursor_text_color {}\n", hex(p.cursor_text));
+    for (i, rgb) in p.ansi.iter().enumerate() {
+        let _ = writeln!(out, "color{i} {}", hex(*rgb));
+    }
+    out
+}
+
+fn ghostty(p: &Palette) -> String {
+    let mut out = format!("# {}, generated by json2term\n\n", p.name);
+    for (i, rgb) in p.ansi.iter().enumerate() {
+        let _ = writeln!(out, "palette = {i}={}", hex(*rgb));
+    }
+    let _ = writeln!(out, "background = {}", hex(p.background));
+    let _ = writeln!(out, "foreground = {}", hex(p.foreground));
+    let _ = writeln!(out, "cursor-color = {}", hex(p.cursor));
+    let _ = writeln!(out, "cursor-text = {}", hex(p.cursor_text));
+    let _ = writeln!(
+        out,
+        "selection-background = {}",
+        hex(p.selection_background)
+    );
+    if let Some(rgb) = p.selection_foreground {
+        let _ = writeln!(out, "selection-foreground = {}", hex(rgb));
+    }
+    out
+}
+
+fn wezterm(p: &Palette) -> String {
+    let list = |colors: &[[u8; 3]]| {
+        colors
+            .iter()
+            .map(|rgb| quote(&hex(*rgb)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut out = format!("# {}, generated by json2term\n\n[colors]\n", p.name);
+    let _ = writeln!(out, "foreground = \"{}\"", hex(p.foreground));
+    let _ = writeln!(out, "background = \"{}\"", hex(p.background));
+    let _ = writeln!(out, "cursor_bg = \"{}\"", hex(p.cursor));
+    let _ = writeln!(out, "cursor_border = \"{}\"", hex(p.cursor));
+    let _ = writeln!(out, "cursor_fg = \"{}\"", hex(p.cursor_text));
+    let _ = writeln!(out, "selection_bg = \"{}\"", hex(p.selection_background));
+    if let Some(rgb) = p.selection_foreground {
+        let _ = writeln!(out, "selection_fg = \"{}\"", hex(rgb));
+    }
+    let _ = writeln!(out, "ansi = [{}]", list(&p.ansi[..8]));
+    let _ = writeln!(out, "brights = [{}]", list(&p.ansi[8..]));
+    // wezterm lists the scheme under this name
+    let _ = writeln!(
+        out,
+        "\n[metadata]\nname = {}\nauthor = {}",
+        quote(&p.name),
+        quote(AUTHOR)
+    );
+    out
+}
+
+fn foot(p: &Palette) -> String {
+    // foot wants colors without the `#`
+    let bare = |rgb: [u8; 3]| hex(rgb)[1..].to_string();
+    let mut out = format!("# {}, generated by json2term\n\n", p.name);
+    let _ = writeln!(
+        out,
+        "[cursor]\ncolor={} {}\n",
+        bare(p.cursor_text),
+        bare(p.cursor)
+    );
+    out.push_str("[colors]\n");
+    let _ = writeln!(out, "foreground={}", bare(p.foreground));
+    let _ = writeln!(out, "background={}", bare(p.background));
+    for (i, rgb) in p.ansi.iter().enumerate() {
+        let (half, n) = if i < 8 {
+            ("regular", i)
+        } else {
+            ("bright", i - 8)
+        };
+        let _ = writeln!(out, "{half}{n}={}", bare(*rgb));
+    }
+    if let Some(rgb) = p.selection_foreground {
+        let _ = writeln!(out, "selection-foreground={}", bare(rgb));
+    }
+    let _ = writeln!(out, "selection-background={}", bare(p.selection_background));
+    out
+}
+
+/// an iterm2 color: srgb components in 0..=1
+#[derive(Serialize)]
+struct ItermColor {
+    #[serde(rename = "Alpha Component")]
+    alpha: f64,
+    #[serde(rename = "Blue Component")]
+    blue: f64,
+    #[serde(rename = "Color Space")]
+    color_space: &'static str,
+    #[serde(rename = "Green Component")]
+    green: f64,
+    #[serde(rename = "Red Component")]
+    red: f64,
+}
+
+impl ItermColor {
+    fn new(rgb: [u8; 3]) -> Self {
+        // rounded like json2xccolor so output doesn't churn on float noise
+        let q = |c: u8| (f64::from(c) / 255.0 * 1_000_000.0).round() / 1_000_000.0;
+        Self {
+            alpha: 1.0,
+            blue: q(rgb[2]),
+            color_space: "sRGB",
+            green: q(rgb[1]),
+            red: q(rgb[0]),
+        }
+    }
+}
+
+fn iterm2(p: &Palette) -> io::Result<String> {
+    let mut colors = BTreeMap::new();
+    for (i, rgb) in p.ansi.iter().enumerate() {
+        colors.insert(format!("Ansi {i} Color"), ItermColor::new(*rgb));
+    }
+    let selected_text = p.selection_foreground.unwrap_or(p.foreground);
+    for (key, rgb) in [
+        ("Background Color", p.background),
+        ("Foreground Color", p.foreground),
+        ("Bold Color", p.foreground),
+        ("Cursor Color", p.cursor),
+        ("Cursor Text Color", p.cursor_text),
+        ("Selection Color", p.selection_background),
+        ("Selected Text Color", selected_text),
+    ] {
+        colors.insert(key.to_string(), ItermColor::new(rgb));
+    }
+
+    let mut out = Vec::new();
+    to_writer_xml(&mut out, &colors).map_err(io::Error::other)?;
+    out.push(b'\n');
+    String::from_utf8(out).map_err(io::Error::other)
+}
+
+/// a windows terminal color scheme, field order as in its settings docs
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WindowsTerminalScheme<'a> {
+    name: &'a str,
+    background: String,
+    foreground: String,
+    cursor_color: String,
+    selection_background: String,
+    black: String,
+    red: String,
+    green: String,
+    yellow: String,
+    blue: String,
+    purple: String,
+    cyan: String,
+    white: String,
+    bright_black: String,
+    bright_red: String,
+    bright_green: String,
+    bright_yellow: String,
+    bright_blue: String,
+    bright_purple: String,
+    bright_cyan: String,
+    bright_white: String,
+}
+
+#[derive(Serialize)]
+struct WindowsTerminalFragment<'a> {
+    schemes: [WindowsTerminalScheme<'a>; 1],
+}
+
+// a json fragment extension, windows terminal loads `schemes` from any file in
+// `%LOCALAPPDATA%\Microsoft\Windows Terminal\Fragments\<app>\`
+fn windows_terminal(p: &Palette) -> io::Result<String> {
+    let c = |i: usize| hex(p.ansi[i]);
+    let scheme = WindowsTerminalScheme {
+        name: &p.name,
+        background: hex(p.background),
+        foreground: hex(p.foreground),
+        cursor_color: hex(p.cursor),
+        selection_background: hex(p.selection_background),
+        black: c(0),
+        red: c(1),
+        green: c(2),
+        yellow: c(3),
+        blue: c(4),
+        purple: c(5),
+        cyan: c(6),
+        white: c(7),
+        bright_black: c(8),
+        bright_red: c(9),
+        bright_green: c(10),
+        bright_yellow: c(11),
+        bright_blue: c(12),
+        bright_purple: c(13),
+        bright_cyan: c(14),
+        bright_white: c(15),
+    };
+    let fragment = WindowsTerminalFragment { schemes: [scheme] };
+    let mut out = serde_json::to_string_pretty(&fragment)?;
+    out.push('\n');
+    Ok(out)
+}
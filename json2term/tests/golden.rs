@@ -0,0 +1,40 @@
+use std::{path::Path, process::Command};
+
+// runs json2term on the fixture theme, the scheme goes to stdout
+fn convert(format: &str) -> String {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let out = Command::new(env!("CARGO_BIN_EXE_json2term"))
+        .args(["--format", format])
+        .arg(fixtures.join("theme.json"))
+        .arg("-")
+        .output()
+        .expect("failed to run json2term");
+    assert!(
+        out.status.success(),
+        "json2term --format {format} failed: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).expect("output isn't utf-8")
+}
+
+fn expected(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+// per-channel float components in srgb, translucent selection flattened onto the background
+#[test]
+fn iterm2_matches_golden() {
+    assert_eq!(convert("iterm2"), expected("expected.itermcolors"));
+}
+
+// a `schemes` fragment with one scheme, keys in the order of windows terminal's docs
+#[test]
+fn windows_terminal_matches_golden() {
+    assert_eq!(
+        convert("windows-terminal"),
+        expected("expected-windows-terminal.json")
+    );
+}
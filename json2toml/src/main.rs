@@ -0,0 +1,414 @@
+use json_comments::StripComments;
+use oxocarbon_utils::{
+    format_hex_color, pack_rgb, parse_hex_rgba_u8 as parse_hex_color, rgb_to_oklab, rgb_to_oklch,
+};
+use serde_json::{Map, Value};
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, Read, Write},
+    process,
+};
+
+// oklab distance under which two colors count as the same palette entry. exact by default
+// so the manifest compiles back to the input, `--merge` trades that for a smaller palette
+const DEFAULT_MERGE: f32 = 0.0;
+// below this oklch chroma a palette entry is named as a gray
+const NEUTRAL_CHROMA: f32 = 0.03;
+
+// upper oklch hue bound of each name, the last wraps around to the first
+const HUE_NAMES: [(f32, &str); 8] = [
+    (15.0, "magenta"),
+    (45.0, "red"),
+    (75.0, "orange"),
+    (120.0, "yellow"),
+    (170.0, "green"),
+    (220.0, "cyan"),
+    (275.0, "blue"),
+    (340.0, "purple"),
+];
+
+// settings are written in this order, anything else after them
+const SETTINGS_ORDER: [&str; 3] = ["foreground", "background", "fontStyle"];
+
+struct Args {
+    input: String,
+    output: String,
+    palette: bool,
+    merge: f32,
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: json2toml [--palette] [--merge <delta-e>] <input.json|-> <output.toml|->");
+    process::exit(2);
+}
+
+fn parse_args() -> Args {
+    let mut palette = false;
+    let mut merge = DEFAULT_MERGE;
+    let mut paths = Vec::with_capacity(2);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--palette" => palette = true,
+            "--merge" => {
+                merge = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|v: &f32| *v >= 0.0)
+                    .unwrap_or_else(|| {
+                        eprintln!("Expected an oklab distance after --merge, e.g. 0.02");
+                        process::exit(2);
+                    });
+                palette = true;
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let [input, output]: [String; 2] = paths.try_into().unwrap_or_else(|_| usage());
+    Args {
+        input,
+        output,
+        palette,
+        merge,
+    }
+}
+
+fn main() -> io::Result<()> {
+    let args = parse_args();
+
+    let reader: Box<dyn Read> = match args.input.as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+    let mut src = String::new();
+    StripComments::new(reader).read_to_string(&mut src)?;
+    let theme: Value = serde_json::from_str(&strip_trailing_commas(&src))?;
+    let Value::Object(mut theme) = theme else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Expected a JSON object",
+        ));
+    };
+    if theme.contains_key("include") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "`include` isn't resolved, decompile the included theme and add it to the manifest's `include` list",
+        ));
+    }
+    theme.remove("$schema");
+    theme.values_mut().for_each(drop_nulls);
+
+    let palette = if args.palette {
+        Palette::infer(&theme, args.merge)
+    } else {
+        Palette::default()
+    };
+    let out = write_manifest(&theme, &palette);
+
+    match args.output.as_str() {
+        "-" => io::stdout().lock().write_all(out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+/// colors used more than once, merged when within `merge` of each other in oklab. merged
+/// members are written as their representative, so only a nonzero `merge` changes colors
+#[derive(Default)]
+struct Palette {
+    // name and representative, in the order they're written
+    entries: Vec<(String, [u8; 3])>,
+    // packed rgb of every member -> index into `entries`
+    members: HashMap<u32, usize>,
+}
+
+struct Cluster {
+    // the most used member, which every other member is written as
+    rep: [u8; 3],
+    lab: [f32; 3],
+    uses: usize,
+    members: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    fn infer(theme: &Map<String, Value>, merge: f32) -> Self {
+        let mut counts: HashMap<u32, ([u8; 3], usize)> = HashMap::new();
+        for value in theme.values() {
+            walk_strings(value, &mut |s| {
+                if let Some((rgb, _)) = parse_hex_color(s) {
+                    counts.entry(pack_rgb(rgb)).or_insert((rgb, 0)).1 += 1;
+                }
+            });
+        }
+        // most used first, so each cluster is represented by its most common member
+        let mut colors: Vec<([u8; 3], usize)> = counts.into_values().collect();
+        colors.sort_by(|a, b| b.1.cmp(&a.1).then(pack_rgb(a.0).cmp(&pack_rgb(b.0))));
+
+        let mut clusters: Vec<Cluster> = Vec::new();
+        for (rgb, count) in colors {
+            let lab = rgb_to_oklab(rgb);
+            match clusters.iter_mut().find(|c| distance(c.lab, lab) <= merge) {
+                Some(cluster) => {
+                    cluster.uses += count;
+                    cluster.members.push(rgb);
+                }
+                None => clusters.push(Cluster {
+                    rep: rgb,
+                    lab,
+                    uses: count,
+                    members: vec![rgb],
+                }),
+            }
+        }
+        clusters.retain(|c| c.uses > 1);
+        // grays dark to light, then accents around the hue wheel
+        clusters.sort_by(|a, b| {
+            sort_key(a.rep)
+                .partial_cmp(&sort_key(b.rep))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut palette = Self::default();
+        let mut taken: HashMap<String, usize> = HashMap::new();
+        for Cluster { rep, members, .. } in clusters {
+            let base = color_name(rep);
+            let n = taken.entry(base.clone()).or_insert(0);
+            *n += 1;
+            let name = if *n == 1 { base } else { format!("{base}-{n}") };
+            for rgb in members {
+                palette.members.insert(pack_rgb(rgb), palette.entries.len());
+            }
+            palette.entries.push((name, rep));
+        }
+        palette
+    }
+
+    // opaque members become `$name`, translucent ones keep their alpha on the representative
+    fn reference(&self, s: &str) -> Option<String> {
+        let (rgb, alpha) = parse_hex_color(s)?;
+        let (name, rep) = &self.entries[*self.members.get(&pack_rgb(rgb))?];
+        Some(match alpha {
+            None => format!("${name}"),
+            Some(_) => format_hex_color(*rep, alpha),
+        })
+    }
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+fn sort_key(rgb: [u8; 3]) -> (u8, f32, f32) {
+    let [l, c, h] = rgb_to_oklch(rgb);
+    if c < NEUTRAL_CHROMA {
+        (0, l, 0.0)
+    } else {
+        (1, h, l)
+    }
+}
+
+// the hue (or gray) and how dark it is from 0 to 100, e.g. `gray81` for #161616
+fn color_name(rgb: [u8; 3]) -> String {
+    let [l, c, h] = rgb_to_oklch(rgb);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let step = ((1.0 - l.clamp(0.0, 1.0)) * 100.0).round() as u8;
+    let hue = if c < NEUTRAL_CHROMA {
+        "gray"
+    } else {
+        HUE_NAMES
+            .iter()
+            .find(|(bound, _)| h < *bound)
+            .map_or(HUE_NAMES[0].1, |(_, name)| name)
+    };
+    format!("{hue}{step}")
+}
+
+fn write_manifest(theme: &Map<String, Value>, palette: &Palette) -> String {
+    let mut out = String::new();
+    let color = |s: &str| palette.reference(s).unwrap_or_else(|| s.to_string());
+
+    for key in ["name", "type"] {
+        if let Some(v) = theme.get(key) {
+            out.push_str(&format!("{key} = {}\n", inline(v, &color)));
+        }
+    }
+    for (key, v) in theme {
+        if !matches!(key.as_str(), "name" | "type") && !v.is_object() && key != "tokenColors" {
+            out.push_str(&format!("{} = {}\n", toml_key(key), inline(v, &color)));
+        }
+    }
+
+    if !palette.entries.is_empty() {
+        out.push_str("\n[palette]\n");
+        for (name, rgb) in &palette.entries {
+            out.push_str(&format!("{name} = \"{}\"\n", format_hex_color(*rgb, None)));
+        }
+    }
+
+    if let Some(Value::Object(colors)) = theme.get("colors") {
+        out.push_str("\n[colors]\n");
+        // dotless keys first, then one block per `prefix.`
+        let mut last_prefix = None;
+        let mut keys: Vec<&String> = colors.keys().collect();
+        keys.sort_by_key(|k| (k.contains('.'), prefix(k), k.as_str()));
+        for key in keys {
+            let p = key.contains('.').then(|| prefix(key));
+            if last_prefix.is_some_and(|last| last != p) {
+                out.push('\n');
+            }
+            last_prefix = Some(p);
+            out.push_str(&format!(
+                "{} = {}\n",
+                toml_key(key),
+                inline(&colors[key], &color)
+            ));
+        }
+    }
+
+    for (key, v) in theme {
+        if let Value::Object(table) = v
+            && key != "colors"
+        {
+            out.push_str(&format!("\n[{}]\n", toml_key(key)));
+            for (k, v) in table {
+                out.push_str(&format!("{} = {}\n", toml_key(k), inline(v, &color)));
+            }
+        }
+    }
+
+    if let Some(Value::Array(tokens)) = theme.get("tokenColors") {
+        for token in tokens {
+            out.push_str("\n[[tokenColors]]\n");
+            write_token(&mut out, token, &color);
+        }
+    }
+    out
+}
+
+fn write_token(out: &mut String, token: &Value, color: &impl Fn(&str) -> String) {
+    if let Some(name) = token.get("name") {
+        out.push_str(&format!("name = {}\n", inline(name, color)));
+    }
+    // `scope` is a comma separated string in most themes, always a list in the manifest
+    let scopes: Vec<Value> = match token.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| Value::String(s.into()))
+            .collect(),
+        Some(Value::Array(a)) => a.clone(),
+        _ => Vec::new(),
+    };
+    if !scopes.is_empty() {
+        out.push_str(&format!(
+            "scope = {}\n",
+            inline(&Value::Array(scopes), color)
+        ));
+    }
+    if let Some(Value::Object(settings)) = token.get("settings") {
+        let mut keys: Vec<&String> = settings.keys().collect();
+        keys.sort_by_key(|k| {
+            SETTINGS_ORDER
+                .iter()
+                .position(|o| o == k)
+                .unwrap_or(SETTINGS_ORDER.len())
+        });
+        let fields: Vec<String> = keys
+            .into_iter()
+            .map(|k| format!("{} = {}", toml_key(k), inline(&settings[k], color)))
+            .collect();
+        out.push_str(&format!("settings = {{ {} }}\n", fields.join(", ")));
+    }
+    for (key, v) in token.as_object().into_iter().flatten() {
+        if !matches!(key.as_str(), "name" | "scope" | "settings") {
+            out.push_str(&format!("{} = {}\n", toml_key(key), inline(v, color)));
+        }
+    }
+}
+
+// `editorGroupHeader.tabsBackground` -> `editorGroupHeader`
+fn prefix(key: &str) -> &str {
+    key.split_once('.').map_or(key, |(p, _)| p)
+}
+
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+    {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+// json string escapes are valid toml basic string escapes
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+fn inline(v: &Value, color: &impl Fn(&str) -> String) -> String {
+    match v {
+        // dropped on load
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote(&color(s)),
+        Value::Array(a) => {
+            let items: Vec<String> = a.iter().map(|v| inline(v, color)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(o) => {
+            let fields: Vec<String> = o
+                .iter()
+                .map(|(k, v)| format!("{} = {}", toml_key(k), inline(v, color)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+// toml has no null, so null members are left out as if they were never set
+fn drop_nulls(v: &mut Value) {
+    match v {
+        Value::Array(a) => {
+            a.retain(|v| !v.is_null());
+            a.iter_mut().for_each(drop_nulls);
+        }
+        Value::Object(o) => {
+            o.retain(|_, v| !v.is_null());
+            o.values_mut().for_each(drop_nulls);
+        }
+        _ => {}
+    }
+}
+
+fn walk_strings(v: &Value, f: &mut impl FnMut(&str)) {
+    match v {
+        Value::String(s) => f(s),
+        Value::Array(a) => a.iter().for_each(|v| walk_strings(v, f)),
+        Value::Object(o) => o.values().for_each(|v| walk_strings(v, f)),
+        _ => {}
+    }
+}
+
+// jsonc allows a comma before `}` or `]`, json doesn't
+fn strip_trailing_commas(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, ch) in src.char_indices() {
+        if in_string {
+            in_string = ch != '"' || escaped;
+            escaped = ch == '\\' && !escaped;
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == ',' && src[i + 1..].trim_start().starts_with(['}', ']']) {
+            continue;
+        }
+        out.push(ch);
+    }
+    out
+}
@@ -0,0 +1,507 @@
+use oxocarbon_utils::{
+    composite_over, luminance_from_u8, nearest_ansi16, nearest_xterm256,
+    parse_hex_rgba_u8 as parse_hex,
+};
+use serde_json::{Map, Value};
+use std::{env, fmt::Write as _, fs, io, path::Path};
+
+/// the part of a highlight group a vscode color sets
+enum Part {
+    Fg,
+    Bg,
+    /// an undercurl in the color
+    Sp,
+}
+
+// (group, part, vscode color keys): the first key the theme sets wins
+const UI_GROUPS: &[(&str, Part, &[&str])] = &[
+    ("Normal", Part::Fg, &["editor.foreground", "foreground"]),
+    ("Normal", Part::Bg, &["editor.background"]),
+    (
+        "Cursor",
+        Part::Fg,
+        &["editorCursor.background", "editor.background"],
+    ),
+    ("Cursor", Part::Bg, &["editorCursor.foreground"]),
+    (
+        "CursorLine",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    (
+        "CursorColumn",
+        Part::Bg,
+        &[
+            "editor.lineHighlightBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    ("ColorColumn", Part::Bg, &["editorRuler.foreground"]),
+    ("LineNr", Part::Fg, &["editorLineNumber.foreground"]),
+    (
+        "CursorLineNr",
+        Part::Fg,
+        &["editorLineNumber.activeForeground", "editor.foreground"],
+    ),
+    (
+        "SignColumn",
+        Part::Bg,
+        &["editorGutter.background", "editor.background"],
+    ),
+    (
+        "FoldColumn",
+        Part::Fg,
+        &["editorGutter.foldingControlForeground"],
+    ),
+    (
+        "Folded",
+        Part::Bg,
+        &[
+            "editor.foldBackground",
+            "editor.selectionHighlightBackground",
+        ],
+    ),
+    ("Visual", Part::Bg, &["editor.selectionBackground"]),
+    ("Search", Part::Fg, &["editor.findMatchHighlightForeground"]),
+    ("Search", Part::Bg, &["editor.findMatchHighlightBackground"]),
+    ("IncSearch", Part::Fg, &["editor.findMatchForeground"]),
+    ("IncSearch", Part::Bg, &["editor.findMatchBackground"]),
+    ("CurSearch", Part::Fg, &["editor.findMatchForeground"]),
+    ("CurSearch", Part::Bg, &["editor.findMatchBackground"]),
+    // editorBracketMatch.background is transparent in oxocarbon
+    ("MatchParen", Part::Bg, &["editor.selectionBackground"]),
+    ("Pmenu", Part::Fg, &["editorSuggestWidget.foreground"]),
+    ("Pmenu", Part::Bg, &["editorSuggestWidget.background"]),
+    (
+        "PmenuSel",
+        Part::Fg,
+        &["editorSuggestWidget.selectedForeground"],
+    ),
+    (
+        "PmenuSel",
+        Part::Bg,
+        &["editorSuggestWidget.selectedBackground"],
+    ),
+    ("PmenuSbar", Part::Bg, &["editorSuggestWidget.background"]),
+    (
+        "PmenuThumb",
+        Part::Bg,
+        &[
+            "scrollbarSlider.activeBackground",
+            "minimapSlider.activeBackground",
+        ],
+    ),
+    ("StatusLine", Part::Fg, &["statusBar.foreground"]),
+    ("StatusLine", Part::Bg, &["statusBar.background"]),
+    ("StatusLineNC", Part::Fg, &["tab.inactiveForeground"]),
+    ("StatusLineNC", Part::Bg, &["statusBar.background"]),
+    ("TabLine", Part::Fg, &["tab.inactiveForeground"]),
+    ("TabLine", Part::Bg, &["tab.inactiveBackground"]),
+    ("TabLineSel", Part::Fg, &["tab.activeForeground"]),
+    ("TabLineSel", Part::Bg, &["tab.activeBackground"]),
+    (
+        "TabLineFill",
+        Part::Bg,
+        &["editorGroupHeader.tabsBackground"],
+    ),
+    (
+        "VertSplit",
+        Part::Fg,
+        &["editorGroup.border", "panel.border"],
+    ),
+    (
+        "NonText",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    (
+        "SpecialKey",
+        Part::Fg,
+        &["editorWhitespace.foreground", "editorLineNumber.foreground"],
+    ),
+    ("Directory", Part::Fg, &["textLink.foreground"]),
+    (
+        "ErrorMsg",
+        Part::Fg,
+        &["errorForeground", "editorError.foreground"],
+    ),
+    ("WarningMsg", Part::Fg, &["editorWarning.foreground"]),
+    ("MoreMsg", Part::Fg, &["textLink.foreground"]),
+    ("Question", Part::Fg, &["textLink.foreground"]),
+    ("WildMenu", Part::Bg, &["list.activeSelectionBackground"]),
+    (
+        "QuickFixLine",
+        Part::Bg,
+        &["list.activeSelectionBackground"],
+    ),
+    ("DiffAdd", Part::Bg, &["diffEditor.insertedLineBackground"]),
+    (
+        "DiffDelete",
+        Part::Bg,
+        &["diffEditor.removedLineBackground"],
+    ),
+    ("DiffChange", Part::Bg, &["merge.incomingContentBackground"]),
+    ("DiffText", Part::Bg, &["merge.incomingHeaderBackground"]),
+    ("SpellBad", Part::Sp, &["editorError.foreground"]),
+    ("SpellCap", Part::Sp, &["editorWarning.foreground"]),
+    ("SpellLocal", Part::Sp, &["editorInfo.foreground"]),
+    ("SpellRare", Part::Sp, &["editorHint.foreground"]),
+];
+
+// (group, scopes): each scope is resolved the way textmate would color it, the first scope
+// any rule covers wins. list the most specific scope first
+const SYNTAX_GROUPS: &[(&str, &[&str])] = &[
+    ("Comment", &["comment"]),
+    (
+        "SpecialComment",
+        &["comment.block.documentation", "comment"],
+    ),
+    ("Constant", &["constant.other", "constant"]),
+    ("String", &["string"]),
+    ("Character", &["constant.character", "string"]),
+    ("Number", &["constant.numeric", "constant"]),
+    ("Float", &["constant.numeric.float", "constant.numeric"]),
+    (
+        "Boolean",
+        &["constant.language.boolean", "constant.language"],
+    ),
+    ("Identifier", &["variable"]),
+    ("Function", &["entity.name.function"]),
+    ("Statement", &["keyword"]),
+    (
+        "Conditional",
+        &["keyword.control.conditional", "keyword.control"],
+    ),
+    ("Repeat", &["keyword.control.loop", "keyword.control"]),
+    ("Label", &["entity.name.label", "label"]),
+    ("Operator", &["keyword.operator"]),
+    ("Keyword", &["keyword"]),
+    (
+        "Exception",
+        &["keyword.control.exception", "keyword.control"],
+    ),
+    ("PreProc", &["keyword.control.directive", "preproc"]),
+    (
+        "Include",
+        &["keyword.control.import", "storage.modifier.import"],
+    ),
+    ("Define", &["keyword.control.directive", "preproc"]),
+    (
+        "Macro",
+        &["entity.name.function.macro", "entity.name.function"],
+    ),
+    ("Type", &["entity.name.type", "entity"]),
+    ("StorageClass", &["storage.modifier"]),
+    ("Structure", &["storage.type"]),
+    ("Typedef", &["entity.name.type", "entity"]),
+    ("Special", &["support"]),
+    ("SpecialChar", &["constant.character.escape"]),
+    ("Tag", &["entity.name.tag"]),
+    ("Delimiter", &["punctuation.separator", "punctuation"]),
+    ("Underlined", &["markup.underline.link"]),
+    ("Error", &["invalid.illegal", "invalid"]),
+    ("Title", &["markup.heading", "entity.name.section.markdown"]),
+];
+
+// g:terminal_ansi_colors and the 16 color fallback, in ansi order
+const TERMINAL_COLORS: [&str; 16] = [
+    "terminal.ansiBlack",
+    "terminal.ansiRed",
+    "terminal.ansiGreen",
+    "terminal.ansiYellow",
+    "terminal.ansiBlue",
+    "terminal.ansiMagenta",
+    "terminal.ansiCyan",
+    "terminal.ansiWhite",
+    "terminal.ansiBrightBlack",
+    "terminal.ansiBrightRed",
+    "terminal.ansiBrightGreen",
+    "terminal.ansiBrightYellow",
+    "terminal.ansiBrightBlue",
+    "terminal.ansiBrightMagenta",
+    "terminal.ansiBrightCyan",
+    "terminal.ansiBrightWhite",
+];
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let name = match args.iter().position(|a| a == "--name") {
+        Some(i) if i + 1 < args.len() => {
+            let name = args.remove(i + 1);
+            args.remove(i);
+            Some(name)
+        }
+        Some(_) => usage(),
+        None => None,
+    };
+    if args.len() != 2 {
+        usage();
+    }
+
+    // the colorscheme name has to match the file vim finds it in
+    let name = name
+        .or_else(|| {
+            (args[1] != "-")
+                .then(|| Path::new(&args[1]).file_stem()?.to_str().map(String::from))
+                .flatten()
+        })
+        .unwrap_or_else(|| "oxocarbon".to_string());
+
+    let reader: Box<dyn io::Read> = match args[0].as_str() {
+        "-" => Box::new(io::stdin().lock()),
+        path => Box::new(fs::File::open(path)?),
+    };
+
+    let theme: Value = serde_json::from_reader(reader)?;
+    let out = colorscheme(&theme, &name)?;
+
+    match args[1].as_str() {
+        "-" => io::Write::write_all(&mut io::stdout().lock(), out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: json2vim [--name <colorscheme>] <input.json|-> <output.vim|->");
+    std::process::exit(2);
+}
+
+#[derive(Default)]
+struct Highlight {
+    fg: Option<[u8; 3]>,
+    bg: Option<[u8; 3]>,
+    sp: Option<[u8; 3]>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl Highlight {
+    fn set(&mut self, part: &Part, color: [u8; 3]) {
+        match part {
+            Part::Fg => self.fg = Some(color),
+            Part::Bg => self.bg = Some(color),
+            Part::Sp => self.sp = Some(color),
+        }
+    }
+
+    fn set_font_style(&mut self, font_style: &str) {
+        for style in font_style.split_whitespace() {
+            match style.to_ascii_lowercase().as_str() {
+                "bold" => self.bold = true,
+                "italic" => self.italic = true,
+                "underline" => self.underline = true,
+                "strikethrough" => self.strikethrough = true,
+                _ => {}
+            }
+        }
+    }
+
+    // `gui=` and `cterm=` values, terminals without undercurl get an underline
+    fn attrs(&self) -> (String, String) {
+        let mut attrs = Vec::new();
+        if self.bold {
+            attrs.push("bold");
+        }
+        if self.italic {
+            attrs.push("italic");
+        }
+        if self.strikethrough {
+            attrs.push("strikethrough");
+        }
+        let mut cterm = attrs.clone();
+        if self.sp.is_some() {
+            attrs.push("undercurl");
+            cterm.push("underline");
+        } else if self.underline {
+            attrs.push("underline");
+            cterm.push("underline");
+        }
+        (attrs.join(","), cterm.join(","))
+    }
+
+    // truecolor and xterm-256 values
+    fn hi_256(&self, group: &str) -> String {
+        let mut hi = format!("hi {group}");
+        for (key, color) in [("guifg", self.fg), ("guibg", self.bg), ("guisp", self.sp)] {
+            if let Some([r, g, b]) = color {
+                let _ = write!(hi, " {key}=#{r:02x}{g:02x}{b:02x}");
+            }
+        }
+        for (key, color) in [("ctermfg", self.fg), ("ctermbg", self.bg)] {
+            if let Some(color) = color {
+                let _ = write!(hi, " {key}={}", nearest_xterm256(color));
+            }
+        }
+        let (gui, cterm) = self.attrs();
+        if !gui.is_empty() {
+            let _ = write!(hi, " gui={gui}");
+        }
+        if !cterm.is_empty() {
+            let _ = write!(hi, " cterm={cterm}");
+        }
+        hi
+    }
+
+    // the nearest of the theme's own terminal colors
+    fn hi_16(&self, group: &str, ansi: &[[u8; 3]; 16]) -> String {
+        let mut hi = format!("hi {group}");
+        for (key, color) in [("ctermfg", self.fg), ("ctermbg", self.bg)] {
+            if let Some(color) = color {
+                let _ = write!(hi, " {key}={}", nearest_ansi16(color, ansi));
+            }
+        }
+        let (_, cterm) = self.attrs();
+        if !cterm.is_empty() {
+            let _ = write!(hi, " cterm={cterm}");
+        }
+        hi
+    }
+}
+
+fn colorscheme(theme: &Value, name: &str) -> io::Result<String> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let theme_name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let tokens = theme["tokenColors"]
+        .as_array()
+        .ok_or_else(|| invalid("Missing tokenColors"))?;
+
+    let background = colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex)
+        .map(|(rgb, _)| rgb)
+        .ok_or_else(|| invalid("Invalid color: editor.background"))?;
+    // vim has no alpha, translucent colors are flattened onto the editor background
+    let flatten =
+        |hex: &str| parse_hex(hex).map(|(rgb, alpha)| composite_over(rgb, alpha, background));
+    let get_color = |keys: &[&str]| get_color(colors, keys).and_then(flatten);
+
+    let ansi: [[u8; 3]; 16] = TERMINAL_COLORS
+        .iter()
+        .map(|key| get_color(&[key]))
+        .collect::<Option<Vec<_>>>()
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| invalid("Missing terminal.ansi* colors"))?;
+
+    // groups in the order they are written
+    let mut groups: Vec<(&str, Highlight)> = Vec::new();
+    let group = |groups: &mut Vec<(&'static str, Highlight)>, name: &'static str| -> usize {
+        groups
+            .iter()
+            .position(|(n, _)| *n == name)
+            .unwrap_or_else(|| {
+                groups.push((name, Highlight::default()));
+                groups.len() - 1
+            })
+    };
+
+    for (name, part, keys) in UI_GROUPS {
+        if let Some(color) = get_color(keys) {
+            let i = group(&mut groups, name);
+            groups[i].1.set(part, color);
+        }
+    }
+
+    for (name, scopes) in SYNTAX_GROUPS {
+        // the font style comes from the same scope as the color
+        let Some((scope, fg)) = scopes
+            .iter()
+            .find_map(|s| Some((s, flatten(resolve(tokens, s, "foreground")?)?)))
+        else {
+            continue;
+        };
+        let i = group(&mut groups, name);
+        groups[i].1.fg = Some(fg);
+        if let Some(font_style) = resolve(tokens, scope, "fontStyle") {
+            groups[i].1.set_font_style(font_style);
+        }
+    }
+
+    let [r, g, b] = background;
+    let mode = if luminance_from_u8(r, g, b) > 0.5 {
+        "light"
+    } else {
+        "dark"
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "\" {theme_name}, generated by json2vim\n");
+    out.push_str("hi clear\nif exists('syntax_on')\n  syntax reset\nendif\n");
+    let _ = writeln!(out, "set background={mode}");
+    let _ = writeln!(out, "let g:colors_name = '{name}'\n");
+
+    out.push_str("if has('gui_running') || &termguicolors || &t_Co >= 256\n");
+    for (name, highlight) in &groups {
+        let _ = writeln!(out, "  {}", highlight.hi_256(name));
+    }
+    let terminal: Vec<_> = ansi
+        .iter()
+        .map(|[r, g, b]| format!("'#{r:02x}{g:02x}{b:02x}'"))
+        .collect();
+    let _ = writeln!(
+        out,
+        "  let g:terminal_ansi_colors = [{}]",
+        terminal.join(", ")
+    );
+    out.push_str("else\n");
+    for (name, highlight) in &groups {
+        let _ = writeln!(out, "  {}", highlight.hi_16(name, &ansi));
+    }
+    out.push_str("endif\n");
+    Ok(out)
+}
+
+fn get_color<'a>(colors: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|k| colors.get(*k).and_then(|v| v.as_str()))
+}
+
+// the `setting` textmate would give `scope`: longest matching selector among rules that
+// set it, later rules winning ties
+fn resolve<'a>(tokens: &'a [Value], scope: &str, setting: &str) -> Option<&'a str> {
+    let scope_matches = |pat: &str| {
+        scope.starts_with(pat)
+            && (scope.len() == pat.len() || scope.as_bytes().get(pat.len()) == Some(&b'.'))
+    };
+    tokens
+        .iter()
+        .filter_map(|item| {
+            let value = item.get("settings")?.get(setting)?.as_str()?;
+            let len = scopes(item)
+                .into_iter()
+                .filter(|s| scope_matches(s))
+                .map(str::len)
+                .max()?;
+            Some((len, value))
+        })
+        .fold(
+            None,
+            |best: Option<(usize, &str)>, (len, value)| match best {
+                Some((best_len, _)) if best_len > len => best,
+                _ => Some((len, value)),
+            },
+        )
+        .map(|(_, value)| value)
+}
+
+fn scopes(item: &Value) -> Vec<&str> {
+    match item.get("scope") {
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::Array(arr)) => arr.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
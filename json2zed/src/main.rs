@@ -0,0 +1,542 @@
+use oxocarbon_utils::{format_hex_color, luminance_from_u8, parse_hex_rgba_u8 as parse_hex_color};
+use serde_json::{Map, Value, json};
+use std::{
+    env, fs,
+    io::{self, Write},
+    process,
+};
+
+const SCHEMA: &str = "https://zed.dev/schema/themes/v0.2.0.json";
+
+// (zed style key, vscode color keys): the first key the theme sets wins. follows zed's
+// `theme_importer`, except where noted
+const STYLE_KEYS: &[(&str, &[&str])] = &[
+    ("background", &["editor.background"]),
+    ("border", &["panel.border"]),
+    ("border.variant", &["panel.border"]),
+    ("border.focused", &["focusBorder"]),
+    ("border.selected", &["panel.border"]),
+    ("border.transparent", &["panel.border"]),
+    ("border.disabled", &["panel.border"]),
+    ("elevated_surface.background", &["dropdown.background"]),
+    ("surface.background", &["panel.background"]),
+    ("element.background", &["button.background"]),
+    ("element.hover", &["list.hoverBackground"]),
+    ("element.selected", &["list.activeSelectionBackground"]),
+    ("drop_target.background", &["list.dropBackground"]),
+    ("ghost_element.hover", &["list.hoverBackground"]),
+    (
+        "ghost_element.selected",
+        &["list.activeSelectionBackground"],
+    ),
+    ("text", &["foreground", "editor.foreground"]),
+    // the importer leaves these unset
+    ("text.accent", &["textLink.foreground"]),
+    ("text.muted", &["editor.foreground"]),
+    ("status_bar.background", &["statusBar.background"]),
+    ("title_bar.background", &["titleBar.activeBackground"]),
+    (
+        "toolbar.background",
+        &["breadcrumb.background", "editor.background"],
+    ),
+    ("tab_bar.background", &["editorGroupHeader.tabsBackground"]),
+    ("tab.inactive_background", &["tab.inactiveBackground"]),
+    (
+        "tab.active_background",
+        &["tab.activeBackground", "tab.inactiveBackground"],
+    ),
+    ("panel.background", &["panel.background"]),
+    ("panel.focused_border", &["list.deemphasizedForeground"]),
+    (
+        "scrollbar.thumb.background",
+        &["scrollbarSlider.background"],
+    ),
+    (
+        "scrollbar.thumb.hover_background",
+        &["scrollbarSlider.hoverBackground"],
+    ),
+    ("scrollbar.thumb.border", &["scrollbarSlider.background"]),
+    ("scrollbar.track.background", &["editor.background"]),
+    ("scrollbar.track.border", &["editorOverviewRuler.border"]),
+    ("editor.foreground", &["editor.foreground", "foreground"]),
+    ("editor.background", &["editor.background"]),
+    ("editor.gutter.background", &["editor.background"]),
+    (
+        "editor.active_line.background",
+        &["editor.lineHighlightBackground"],
+    ),
+    ("editor.line_number", &["editorLineNumber.foreground"]),
+    ("editor.active_line_number", &["editor.foreground"]),
+    ("editor.wrap_guide", &["panel.border"]),
+    ("editor.active_wrap_guide", &["panel.border"]),
+    // the importer reads editorBracketMatch.background, which oxocarbon keeps transparent
+    (
+        "editor.document_highlight.bracket_background",
+        &["editor.selectionBackground"],
+    ),
+    ("terminal.background", &["terminal.background"]),
+    ("terminal.foreground", &["terminal.foreground"]),
+    ("terminal.ansi.black", &["terminal.ansiBlack"]),
+    ("terminal.ansi.red", &["terminal.ansiRed"]),
+    ("terminal.ansi.green", &["terminal.ansiGreen"]),
+    ("terminal.ansi.yellow", &["terminal.ansiYellow"]),
+    ("terminal.ansi.blue", &["terminal.ansiBlue"]),
+    ("terminal.ansi.magenta", &["terminal.ansiMagenta"]),
+    ("terminal.ansi.cyan", &["terminal.ansiCyan"]),
+    ("terminal.ansi.white", &["terminal.ansiWhite"]),
+    ("terminal.ansi.bright_black", &["terminal.ansiBrightBlack"]),
+    ("terminal.ansi.bright_red", &["terminal.ansiBrightRed"]),
+    ("terminal.ansi.bright_green", &["terminal.ansiBrightGreen"]),
+    (
+        "terminal.ansi.bright_yellow",
+        &["terminal.ansiBrightYellow"],
+    ),
+    ("terminal.ansi.bright_blue", &["terminal.ansiBrightBlue"]),
+    (
+        "terminal.ansi.bright_magenta",
+        &["terminal.ansiBrightMagenta"],
+    ),
+    ("terminal.ansi.bright_cyan", &["terminal.ansiBrightCyan"]),
+    ("terminal.ansi.bright_white", &["terminal.ansiBrightWhite"]),
+    // status colors
+    ("created", &["editorGutter.addedBackground"]),
+    ("deleted", &["editorGutter.deletedBackground"]),
+    ("modified", &["editorGutter.modifiedBackground"]),
+    ("error", &["editorError.foreground"]),
+    ("error.background", &["editorError.background"]),
+    ("error.border", &["editorError.border"]),
+    ("warning", &["list.warningForeground"]),
+    ("hidden", &["tab.inactiveForeground"]),
+    ("hint", &["editorInlayHint.foreground"]),
+    ("ignored", &["gitDecoration.ignoredResourceForeground"]),
+];
+
+// compatibility variants mute secondary text instead of keeping it at full contrast
+const COMPAT_MUTED: &str = "disabledForeground";
+
+// (zed syntax token, textmate scopes, fallback token): the token rule matching the most
+// scopes wins, scopes listed first weigh more. a token with no match takes its fallback's
+// style
+const SYNTAX: &[(&str, &[&str], Option<&str>)] = &[
+    ("attribute", &["entity.other.attribute-name"], None),
+    ("boolean", &["constant.language"], None),
+    ("comment", &["comment"], None),
+    (
+        "comment.doc",
+        &["comment.block.documentation"],
+        Some("comment"),
+    ),
+    (
+        "constant",
+        &["constant", "constant.language", "constant.character"],
+        None,
+    ),
+    (
+        "constructor",
+        &[
+            "entity.name.tag",
+            "entity.name.function.definition.special.constructor",
+        ],
+        None,
+    ),
+    ("embedded", &["meta.embedded"], None),
+    ("emphasis", &["markup.italic"], None),
+    (
+        "emphasis.strong",
+        &[
+            "markup.bold",
+            "markup.italic markup.bold",
+            "markup.bold markup.italic",
+        ],
+        None,
+    ),
+    ("enum", &["support.type.enum"], None),
+    (
+        "function",
+        &[
+            "entity.function",
+            "entity.name.function",
+            "variable.function",
+        ],
+        None,
+    ),
+    (
+        "keyword",
+        &[
+            "keyword",
+            "keyword.other.fn.rust",
+            "keyword.control",
+            "keyword.control.fun",
+            "keyword.control.class",
+            "punctuation.accessor",
+            "entity.name.tag",
+        ],
+        None,
+    ),
+    (
+        "label",
+        &[
+            "label",
+            "entity.name",
+            "entity.name.import",
+            "entity.name.package",
+        ],
+        None,
+    ),
+    (
+        "link_text",
+        &["markup.underline.link", "string.other.link"],
+        None,
+    ),
+    (
+        "link_uri",
+        &["markup.underline.link", "string.other.link"],
+        None,
+    ),
+    ("number", &["constant.numeric", "number"], Some("constant")),
+    ("operator", &["operator", "keyword.operator"], None),
+    (
+        "preproc",
+        &[
+            "preproc",
+            "meta.preprocessor",
+            "punctuation.definition.preprocessor",
+        ],
+        None,
+    ),
+    (
+        "property",
+        &[
+            "variable.member",
+            "support.type.property-name",
+            "variable.object.property",
+            "variable.other.field",
+        ],
+        None,
+    ),
+    (
+        "punctuation",
+        &[
+            "punctuation",
+            "punctuation.section",
+            "punctuation.accessor",
+            "punctuation.separator",
+            "punctuation.terminator",
+            "punctuation.definition.tag",
+        ],
+        None,
+    ),
+    (
+        "punctuation.bracket",
+        &[
+            "punctuation.bracket",
+            "punctuation.definition.tag.begin",
+            "punctuation.definition.tag.end",
+        ],
+        Some("punctuation"),
+    ),
+    (
+        "punctuation.delimiter",
+        &[
+            "punctuation.delimiter",
+            "punctuation.separator",
+            "punctuation.terminator",
+        ],
+        Some("punctuation"),
+    ),
+    (
+        "punctuation.list_marker",
+        &["markup.list punctuation.definition.list.begin"],
+        Some("punctuation"),
+    ),
+    (
+        "punctuation.special",
+        &["punctuation.special"],
+        Some("punctuation"),
+    ),
+    ("string", &["string"], None),
+    (
+        "string.escape",
+        &["string.escape", "constant.character", "constant.other"],
+        Some("string"),
+    ),
+    ("string.regex", &["string.regex"], Some("string")),
+    (
+        "string.special",
+        &["string.special", "constant.other.symbol"],
+        Some("string"),
+    ),
+    (
+        "string.special.symbol",
+        &["string.special.symbol", "constant.other.symbol"],
+        Some("string"),
+    ),
+    ("tag", &["tag", "entity.name.tag", "meta.tag.sgml"], None),
+    ("text.literal", &["text.literal", "string"], None),
+    ("title", &["title", "entity.name"], None),
+    (
+        "type",
+        &[
+            "entity.name.type",
+            "entity.name.type.primitive",
+            "entity.name.type.numeric",
+            "keyword.type",
+            "support.type",
+            "support.type.primitive",
+            "support.class",
+        ],
+        None,
+    ),
+    (
+        "variable",
+        &[
+            "variable",
+            "variable.language",
+            "variable.member",
+            "variable.parameter",
+            "variable.parameter.function-call",
+        ],
+        None,
+    ),
+    (
+        "variable.special",
+        &[
+            "variable.special",
+            "variable.member",
+            "variable.annotation",
+            "variable.language",
+        ],
+        Some("variable"),
+    ),
+    ("variant", &["variant"], None),
+];
+
+/// where a syntax token without a textmate counterpart takes its color from
+enum Source {
+    /// a key of `colors`
+    Color(&'static str),
+    /// the best token rule for this scope
+    Scope(&'static str),
+}
+
+// (zed syntax token, source, font weight): tokens zed highlights that vscode themes don't
+// describe, set after the textmate mapping
+const EXTRA_SYNTAX: &[(&str, Source, Option<u16>)] = &[
+    (
+        "link_text",
+        Source::Color("textLink.activeForeground"),
+        None,
+    ),
+    ("selector", Source::Color("editor.foreground"), None),
+    (
+        "selector.pseudo",
+        Source::Color("editorLineNumber.activeForeground"),
+        None,
+    ),
+    ("namespace", Source::Scope("variable"), None),
+    (
+        "function.builtin",
+        Source::Color("textLink.foreground"),
+        Some(500),
+    ),
+];
+
+// (zed syntax token, font weight): `fontStyle` only knows bold, zed takes any weight
+const FONT_WEIGHTS: [(&str, u16); 4] = [
+    ("function", 700),
+    ("constructor", 600),
+    ("emphasis", 500),
+    ("emphasis.strong", 700),
+];
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: json2zed [--name <family>] [--author <name>] <output.json|-> <input.json>..."
+    );
+    process::exit(2);
+}
+
+fn main() -> io::Result<()> {
+    let mut name = String::from("Oxocarbon");
+    let mut author = String::from("Nyoom Engineering");
+    let mut paths = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => name = args.next().unwrap_or_else(|| usage()),
+            "--author" => author = args.next().unwrap_or_else(|| usage()),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() < 2 {
+        usage();
+    }
+    let output = paths.remove(0);
+
+    let mut themes = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let theme: Value = serde_json::from_reader(fs::File::open(path)?)?;
+        themes.push(convert_theme(&theme)?);
+    }
+    let family = json!({
+        "$schema": SCHEMA,
+        "name": name,
+        "author": author,
+        "themes": themes,
+    });
+
+    let out = serde_json::to_string_pretty(&family)? + "\n";
+    match output.as_str() {
+        "-" => io::stdout().lock().write_all(out.as_bytes()),
+        path => fs::write(path, out),
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn is_light(background: [u8; 3]) -> bool {
+    let [r, g, b] = background;
+    luminance_from_u8(r, g, b) > 0.5
+}
+
+fn convert_theme(theme: &Value) -> io::Result<Value> {
+    let name = theme["name"]
+        .as_str()
+        .ok_or_else(|| invalid("Missing name"))?;
+    let colors = theme["colors"]
+        .as_object()
+        .ok_or_else(|| invalid("Missing colors"))?;
+    let tokens = theme["tokenColors"]
+        .as_array()
+        .ok_or_else(|| invalid("Missing tokenColors"))?;
+    // the background decides, `type` isn't always set and high contrast themes spell it
+    // their own way
+    let background = colors
+        .get("editor.background")
+        .and_then(Value::as_str)
+        .and_then(parse_hex_color)
+        .ok_or_else(|| invalid("Missing editor.background"))?;
+    let appearance = if is_light(background.0) {
+        "light"
+    } else {
+        "dark"
+    };
+    let color = |key: &str| colors.get(key).and_then(Value::as_str).and_then(zed_color);
+
+    let mut style = Map::new();
+    for (key, sources) in STYLE_KEYS {
+        if let Some(c) = sources.iter().find_map(|k| color(k)) {
+            style.insert((*key).into(), c.into());
+        }
+    }
+    // the importer's default inlay hint color
+    style.entry("hint").or_insert_with(|| "#969696ff".into());
+    if name.to_ascii_lowercase().contains("compatibility")
+        && let Some(c) = color(COMPAT_MUTED)
+    {
+        style.insert("text.muted".into(), c.into());
+    }
+    // one player, the local cursor
+    let cursor = color("editorCursor.foreground");
+    style.insert(
+        "players".into(),
+        json!([{
+            "cursor": cursor,
+            "background": cursor,
+            "selection": color("inputOption.activeBackground"),
+        }]),
+    );
+    style.insert("syntax".into(), convert_syntax(tokens, &color).into());
+
+    Ok(json!({
+        "name": name,
+        "appearance": appearance,
+        "style": style,
+    }))
+}
+
+fn convert_syntax(tokens: &[Value], color: &impl Fn(&str) -> Option<String>) -> Map<String, Value> {
+    let mut syntax = Map::new();
+    for (token, scopes, fallback) in SYNTAX {
+        let rule = best_match(tokens, scopes).or_else(|| {
+            let (_, scopes, _) = SYNTAX.iter().find(|(t, ..)| Some(*t) == *fallback)?;
+            best_match(tokens, scopes)
+        });
+        if let Some(rule) = rule {
+            syntax.insert((*token).into(), highlight(rule));
+        }
+    }
+
+    for (token, source, weight) in EXTRA_SYNTAX {
+        let c = match source {
+            Source::Color(key) => color(key),
+            Source::Scope(scope) => best_match(tokens, &[scope])
+                .and_then(|rule| rule["settings"]["foreground"].as_str())
+                .and_then(zed_color),
+        };
+        syntax.insert(
+            (*token).into(),
+            json!({ "color": c, "font_style": null, "font_weight": weight }),
+        );
+    }
+
+    for (token, weight) in FONT_WEIGHTS {
+        let entry = syntax
+            .entry(token)
+            .or_insert_with(|| json!({ "color": null, "font_style": null }));
+        entry["font_weight"] = weight.into();
+    }
+    syntax
+}
+
+// rank each rule with a foreground by the scopes it shares with `scopes`, weighted by
+// position. later rules win ties, as in the importer
+fn best_match<'a>(tokens: &'a [Value], scopes: &[&str]) -> Option<&'a Value> {
+    tokens
+        .iter()
+        .filter(|rule| rule["settings"]["foreground"].is_string())
+        .filter_map(|rule| {
+            let own = rule_scopes(rule);
+            let rank: usize = scopes
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| own.contains(s))
+                .map(|(i, _)| 1 + scopes.len() - i)
+                .sum();
+            (rank > 0).then_some((rank, rule))
+        })
+        .max_by_key(|(rank, _)| *rank)
+        .map(|(_, rule)| rule)
+}
+
+fn highlight(rule: &Value) -> Value {
+    let settings = &rule["settings"];
+    let styles = settings["fontStyle"].as_str().unwrap_or_default();
+    let has = |s: &str| styles.split_whitespace().any(|w| w.eq_ignore_ascii_case(s));
+    json!({
+        "color": settings["foreground"].as_str().and_then(zed_color),
+        "font_style": has("italic").then_some("italic"),
+        "font_weight": has("bold").then_some(700),
+    })
+}
+
+// `scope` is an array in compiled oxocarbon themes, a comma separated string in others
+fn rule_scopes(rule: &Value) -> Vec<&str> {
+    match &rule["scope"] {
+        Value::String(s) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Value::Array(arr) => arr.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// zed writes every color with its alpha
+fn zed_color(hex: &str) -> Option<String> {
+    let (rgb, alpha) = parse_hex_color(hex)?;
+    Some(format_hex_color(rgb, Some(alpha.unwrap_or(u8::MAX))))
+}
@@ -127,6 +127,13 @@ fn linear_to_srgb_u8(c: f32) -> u8 {
     (s.mul_add(255.0, 0.5)).clamp(0.0, 255.0) as u8
 }
 
+/// rounds and clamps a 0..255 float channel
+#[inline]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn channel_from_f32(c: f32) -> u8 {
+    c.round().clamp(0.0, 255.0) as u8
+}
+
 #[inline(always)]
 pub fn format_hex_color(rgb: [u8; 3], alpha: Option<u8>) -> String {
     const HEX: &[u8; 16] = b"0123456789abcdef";
@@ -151,8 +158,9 @@ pub fn format_hex_color(rgb: [u8; 3], alpha: Option<u8>) -> String {
     out
 }
 
-/// computes relative luminance
-/// suitable for wcag contrast checks
+/// computes relative luminance on the fast x^2.5 curve
+/// fine for ordering colors and telling light from dark, `contrast_ratio` uses the exact
+/// wcag curve
 #[inline]
 pub fn luminance_from_u8(r: u8, g: u8, b: u8) -> f32 {
     // linearize components before applying luminance weights
@@ -196,6 +204,357 @@ pub fn midpoint_hex_linear(a_hex: &str, b_hex: &str) -> String {
     format_hex_color(rgb, None)
 }
 
+/// wcag contrast ratio between two opaque colors (1.0..=21.0)
+#[must_use]
+pub fn contrast_ratio(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let la = wcag_luminance(a);
+    let lb = wcag_luminance(b);
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+// relative luminance with wcag's exact 2.4 exponent, thresholds near 7:1 are too tight for
+// the fast curve
+fn wcag_luminance(rgb: [u8; 3]) -> f32 {
+    let [r, g, b] = rgb.map(|c| {
+        let c = f32::from(c) * INV_255;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// flattens a translucent color onto an opaque background (sRGB, like the renderer)
+#[must_use]
+pub fn composite_over(rgb: [u8; 3], alpha: Option<u8>, bg: [u8; 3]) -> [u8; 3] {
+    let Some(a) = alpha else {
+        return rgb;
+    };
+    let a = u16::from(a);
+    std::array::from_fn(|i| {
+        let mixed = u16::from(rgb[i]) * a + u16::from(bg[i]) * (255 - a);
+        u8::try_from((mixed + 127) / 255).unwrap_or(u8::MAX)
+    })
+}
+
+/// mixes `rgb` toward white or black until it reaches `min` contrast against `bg`.
+/// picks whichever direction gets there with the smallest shift, otherwise the
+/// extreme with the best ratio
+#[must_use]
+pub fn ensure_contrast(rgb: [u8; 3], bg: [u8; 3], min: f32) -> [u8; 3] {
+    if contrast_ratio(rgb, bg) >= min {
+        return rgb;
+    }
+
+    let mix = |target: u8, t: f32| -> [u8; 3] {
+        rgb.map(|c| {
+            let c = f32::from(c);
+            channel_from_f32(c + (f32::from(target) - c) * t)
+        })
+    };
+
+    let solve = |target: u8| -> Option<(f32, [u8; 3])> {
+        if contrast_ratio([target; 3], bg) < min {
+            return None;
+        }
+        let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+        for _ in 0..16 {
+            let mid = 0.5 * (lo + hi);
+            if contrast_ratio(mix(target, mid), bg) >= min {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Some((hi, mix(target, hi)))
+    };
+
+    match (solve(u8::MAX), solve(0)) {
+        (Some(w), Some(b)) => {
+            if w.0 <= b.0 {
+                w.1
+            } else {
+                b.1
+            }
+        }
+        (Some(w), None) => w.1,
+        (None, Some(b)) => b.1,
+        (None, None) => {
+            if contrast_ratio([u8::MAX; 3], bg) >= contrast_ratio([0; 3], bg) {
+                [u8::MAX; 3]
+            } else {
+                [0; 3]
+            }
+        }
+    }
+}
+
+/// converts an srgb color to oklab `[l, a, b]`
+#[must_use]
+pub fn rgb_to_oklab(rgb: [u8; 3]) -> [f32; 3] {
+    linear_to_oklab(rgb.map(srgb_u8_to_linear))
+}
+
+fn linear_to_oklab(lin: [f32; 3]) -> [f32; 3] {
+    let lms = [
+        0.412_221_46 * lin[0] + 0.536_332_55 * lin[1] + 0.051_445_995 * lin[2],
+        0.211_903_5 * lin[0] + 0.680_699_5 * lin[1] + 0.107_396_96 * lin[2],
+        0.088_302_46 * lin[0] + 0.281_718_85 * lin[1] + 0.629_978_7 * lin[2],
+    ]
+    .map(f32::cbrt);
+    [
+        0.210_454_26 * lms[0] + 0.793_617_8 * lms[1] - 0.004_072_047 * lms[2],
+        1.977_998_5 * lms[0] - 2.428_592_2 * lms[1] + 0.450_593_7 * lms[2],
+        0.025_904_037 * lms[0] + 0.782_771_77 * lms[1] - 0.808_675_77 * lms[2],
+    ]
+}
+
+/// converts oklab back to srgb, clamping out-of-gamut channels
+#[must_use]
+pub fn oklab_to_rgb(lab: [f32; 3]) -> [u8; 3] {
+    oklab_to_linear(lab).map(linear_to_srgb_u8)
+}
+
+fn oklab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    let lms = [
+        lab[0] + 0.396_337_78 * lab[1] + 0.215_803_76 * lab[2],
+        lab[0] - 0.105_561_346 * lab[1] - 0.063_854_17 * lab[2],
+        lab[0] - 0.089_484_18 * lab[1] - 1.291_485_5 * lab[2],
+    ]
+    .map(|c| c * c * c);
+    [
+        4.076_741_7 * lms[0] - 3.307_711_6 * lms[1] + 0.230_969_94 * lms[2],
+        -1.268_438 * lms[0] + 2.609_757_4 * lms[1] - 0.341_319_38 * lms[2],
+        -0.004_196_086_3 * lms[0] - 0.703_418_6 * lms[1] + 1.707_614_7 * lms[2],
+    ]
+}
+
+/// converts an srgb color to oklch `[l, c, h]`, hue in degrees
+#[must_use]
+pub fn rgb_to_oklch(rgb: [u8; 3]) -> [f32; 3] {
+    let [l, a, b] = rgb_to_oklab(rgb);
+    [l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.0)]
+}
+
+/// converts oklch to srgb, reducing chroma until the color fits the gamut
+#[must_use]
+pub fn oklch_to_rgb(lch: [f32; 3]) -> [u8; 3] {
+    const EPS: f32 = 1e-4;
+    let (sin, cos) = lch[2].to_radians().sin_cos();
+    let lab = |c: f32| [lch[0], c * cos, c * sin];
+    let in_gamut = |c: f32| {
+        oklab_to_linear(lab(c))
+            .iter()
+            .all(|v| (-EPS..=1.0 + EPS).contains(v))
+    };
+
+    if in_gamut(lch[1]) {
+        return oklab_to_rgb(lab(lch[1]));
+    }
+    let (mut lo, mut hi) = (0.0_f32, lch[1]);
+    for _ in 0..20 {
+        let mid = 0.5 * (lo + hi);
+        if in_gamut(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    oklab_to_rgb(lab(lo))
+}
+
+/// interpolates two colors in oklab, `t = 0` is `a` and `t = 1` is `b`
+#[must_use]
+pub fn mix_oklab(a: [u8; 3], b: [u8; 3], t: f32) -> [u8; 3] {
+    if t <= 0.0 {
+        return a;
+    }
+    if t >= 1.0 {
+        return b;
+    }
+    let (la, lb) = (rgb_to_oklab(a), rgb_to_oklab(b));
+    oklab_to_rgb(std::array::from_fn(|i| la[i] + (lb[i] - la[i]) * t))
+}
+
+/// perceptual distance between two colors, euclidean in oklab (`ΔEok`)
+#[must_use]
+pub fn delta_e(a: [u8; 3], b: [u8; 3]) -> f32 {
+    delta_e_lab(rgb_to_oklab(a), rgb_to_oklab(b))
+}
+
+#[inline]
+fn delta_e_lab(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+// xterm's default system colors, terminals usually override these
+const XTERM_SYSTEM: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00],
+    [0xcd, 0x00, 0x00],
+    [0x00, 0xcd, 0x00],
+    [0xcd, 0xcd, 0x00],
+    [0x00, 0x00, 0xee],
+    [0xcd, 0x00, 0xcd],
+    [0x00, 0xcd, 0xcd],
+    [0xe5, 0xe5, 0xe5],
+    [0x7f, 0x7f, 0x7f],
+    [0xff, 0x00, 0x00],
+    [0x00, 0xff, 0x00],
+    [0xff, 0xff, 0x00],
+    [0x5c, 0x5c, 0xff],
+    [0xff, 0x00, 0xff],
+    [0x00, 0xff, 0xff],
+    [0xff, 0xff, 0xff],
+];
+
+const XTERM_CUBE: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+/// the rgb of an xterm-256 index: 16 system colors, a 6x6x6 cube, then a 24 step gray ramp
+#[must_use]
+pub fn xterm256_rgb(index: u8) -> [u8; 3] {
+    match index {
+        0..=15 => XTERM_SYSTEM[usize::from(index)],
+        16..=231 => {
+            let i = usize::from(index - 16);
+            [XTERM_CUBE[i / 36], XTERM_CUBE[i / 6 % 6], XTERM_CUBE[i % 6]]
+        }
+        232..=255 => [8 + (index - 232) * 10; 3],
+    }
+}
+
+// oklab of the cube and gray ramp, the system colors are left out since terminals remap them
+static XTERM256_LABS: std::sync::LazyLock<[[f32; 3]; 240]> = std::sync::LazyLock::new(|| {
+    std::array::from_fn(|i| rgb_to_oklab(xterm256_rgb(u8::try_from(16 + i).unwrap_or(u8::MAX))))
+});
+
+/// the xterm-256 index perceptually nearest `rgb`, from the cube and gray ramp (16..=255)
+#[must_use]
+pub fn nearest_xterm256(rgb: [u8; 3]) -> u8 {
+    let i = find_nearest_lab(&*XTERM256_LABS, rgb_to_oklab(rgb));
+    16 + u8::try_from(i).unwrap_or(0)
+}
+
+// ansi black, white, bright black and bright white, the only slots a gray maps to
+const ANSI_GRAYS: [usize; 4] = [0, 7, 8, 15];
+// below this oklch chroma a color counts as a gray
+const ANSI_NEUTRAL_CHROMA: f32 = 0.03;
+
+/// the index of the color in a 16 color ansi `palette` perceptually nearest `rgb`, for
+/// terminals limited to the theme's own `terminal.ansi*` colors. grays stay on the gray
+/// slots, so dim ui text never turns into an accent
+#[must_use]
+pub fn nearest_ansi16(rgb: [u8; 3], palette: &[[u8; 3]; 16]) -> u8 {
+    let target = rgb_to_oklab(rgb);
+    let slots: Vec<usize> = if target[1].hypot(target[2]) < ANSI_NEUTRAL_CHROMA {
+        ANSI_GRAYS.to_vec()
+    } else {
+        (0..16).collect()
+    };
+    let labs: Vec<[f32; 3]> = slots.iter().map(|&i| rgb_to_oklab(palette[i])).collect();
+    u8::try_from(slots[find_nearest_lab(&labs, target)]).unwrap_or(0)
+}
+
+pub type Mat3 = [[f32; 3]; 3];
+
+const SRGB_TO_XYZ: Mat3 = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+
+const XYZ_TO_SRGB: Mat3 = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+const BRADFORD: Mat3 = [
+    [0.895_1, 0.266_4, -0.161_4],
+    [-0.750_2, 1.713_5, 0.036_7],
+    [0.038_9, -0.068_5, 1.029_6],
+];
+
+const BRADFORD_INV: Mat3 = [
+    [0.986_992_9, -0.147_054_3, 0.159_962_7],
+    [0.432_305_3, 0.518_360_3, 0.049_291_2],
+    [-0.008_528_7, 0.040_042_8, 0.968_486_7],
+];
+
+// srgb reference white
+const D65_XY: (f32, f32) = (0.312_71, 0.329_02);
+
+fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    std::array::from_fn(|r| std::array::from_fn(|c| (0..3).map(|k| a[r][k] * b[k][c]).sum()))
+}
+
+fn mat_apply(m: &Mat3, v: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|r| m[r][0] * v[0] + m[r][1] * v[1] + m[r][2] * v[2])
+}
+
+fn xy_to_xyz((x, y): (f32, f32)) -> [f32; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+/// chromaticity of a blackbody at `kelvin` (Kim et al. cubic fit, 1667K..25000K)
+#[must_use]
+pub fn planckian_xy(kelvin: f32) -> (f32, f32) {
+    let t = kelvin.clamp(1667.0, 25000.0);
+    let (t1, t2, t3) = (1e3 / t, 1e6 / (t * t), 1e9 / (t * t * t));
+    let x = if t <= 4000.0 {
+        -0.266_123_9 * t3 - 0.234_358_9 * t2 + 0.877_695_6 * t1 + 0.179_91
+    } else {
+        -3.025_847 * t3 + 2.107_037_9 * t2 + 0.222_634_7 * t1 + 0.240_39
+    };
+    let (x2, x3) = (x * x, x * x * x);
+    let y = if t <= 2222.0 {
+        -1.106_381_4 * x3 - 1.348_110_2 * x2 + 2.185_558_3 * x - 0.202_196_83
+    } else if t <= 4000.0 {
+        -0.954_947_6 * x3 - 1.374_185_9 * x2 + 2.091_37 * x - 0.167_488_67
+    } else {
+        3.081_758 * x3 - 5.873_387 * x2 + 3.751_13 * x - 0.370_014_83
+    };
+    (x, y)
+}
+
+/// bradford chromatic adaptation from D65 to a blackbody white point, as a matrix on
+/// linear sRGB, normalized so the adapted white stays in gamut
+#[must_use]
+pub fn white_point_adaptation(kelvin: f32) -> Mat3 {
+    let src = mat_apply(&BRADFORD, xy_to_xyz(D65_XY));
+    let dst = mat_apply(&BRADFORD, xy_to_xyz(planckian_xy(kelvin)));
+    let mut scale = [[0.0; 3]; 3];
+    for i in 0..3 {
+        scale[i][i] = dst[i] / src[i];
+    }
+    let cone = mat_mul(&BRADFORD_INV, &mat_mul(&scale, &BRADFORD));
+    let m = mat_mul(&XYZ_TO_SRGB, &mat_mul(&cone, &SRGB_TO_XYZ));
+    // the adapted white lands outside srgb, scale it so its largest channel is 1. white
+    // then warms instead of clipping back to #ffffff, and the gray ramp keeps its order
+    let white = mat_apply(&m, [1.0; 3]);
+    let peak = white[0].max(white[1]).max(white[2]);
+    m.map(|row| row.map(|v| v / peak))
+}
+
+/// adapts an srgb color with a matrix from [`white_point_adaptation`]. out-of-gamut
+/// results lose chroma at constant oklch lightness and hue, so accents keep their
+/// relationships instead of clipping per channel
+#[must_use]
+pub fn adapt_rgb(rgb: [u8; 3], adaptation: &Mat3) -> [u8; 3] {
+    const EPS: f32 = 1e-4;
+    let lin = mat_apply(adaptation, rgb.map(srgb_u8_to_linear));
+    if lin.iter().all(|v| (-EPS..=1.0 + EPS).contains(v)) {
+        return lin.map(linear_to_srgb_u8);
+    }
+    let [l, a, b] = linear_to_oklab(lin.map(|v| v.max(0.0)));
+    oklch_to_rgb([
+        l.min(1.0),
+        a.hypot(b),
+        b.atan2(a).to_degrees().rem_euclid(360.0),
+    ])
+}
+
 #[inline]
 pub fn pack_rgb(rgb: [u8; 3]) -> u32 {
     (u32::from(rgb[0]) << 16) | (u32::from(rgb[1]) << 8) | u32::from(rgb[2])
@@ -316,3 +675,103 @@ fn nearest_index_scaler(luminances: &[f32], target: f32) -> usize {
 
     best_idx
 }
+
+/// index of the oklab color in `labs` nearest `target`, see [`delta_e`]
+#[inline]
+#[must_use]
+pub fn find_nearest_lab(labs: &[[f32; 3]], target: [f32; 3]) -> usize {
+    if labs.is_empty() {
+        return 0;
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    {
+        return unsafe { nearest_lab_neon(labs, target) };
+    }
+
+    #[cfg(not(all(target_arch = "aarch64", target_feature = "neon")))]
+    {
+        nearest_lab_scaler(labs, target)
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[inline]
+#[target_feature(enable = "neon")]
+pub unsafe fn nearest_lab_neon(labs: &[[f32; 3]], target: [f32; 3]) -> usize {
+    use std::arch::aarch64::*;
+
+    let len = labs.len();
+    if len < 4 {
+        return nearest_lab_scaler(labs, target);
+    }
+
+    // squared distances rank the same as delta_e, so the sqrt is skipped
+    let ptr = labs.as_ptr().cast::<f32>();
+    let (tl, ta, tb) = (
+        vdupq_n_f32(target[0]),
+        vdupq_n_f32(target[1]),
+        vdupq_n_f32(target[2]),
+    );
+    let mut best_idx = 0;
+    let mut best_diff = f32::INFINITY;
+
+    let chunks = len / 4;
+    for chunk in 0..chunks {
+        let i = chunk * 4;
+        // deinterleaves four [l, a, b] triples into l, a and b lanes
+        let v = unsafe { vld3q_f32(ptr.add(i * 3)) };
+        let dl = vsubq_f32(v.0, tl);
+        let da = vsubq_f32(v.1, ta);
+        let db = vsubq_f32(v.2, tb);
+        let diff = vfmaq_f32(vfmaq_f32(vmulq_f32(dl, dl), da, da), db, db);
+
+        let min_in_vec = vminvq_f32(diff);
+        if min_in_vec >= best_diff {
+            continue;
+        }
+
+        best_diff = min_in_vec;
+        let min_mask = vceqq_f32(diff, vdupq_n_f32(min_in_vec));
+
+        if vgetq_lane_u32(min_mask, 0) != 0 {
+            best_idx = i;
+        } else if vgetq_lane_u32(min_mask, 1) != 0 {
+            best_idx = i + 1;
+        } else if vgetq_lane_u32(min_mask, 2) != 0 {
+            best_idx = i + 2;
+        } else {
+            best_idx = i + 3;
+        }
+
+        if best_diff == 0.0 {
+            return best_idx;
+        }
+    }
+
+    for (i, lab) in labs.iter().enumerate().skip(chunks * 4) {
+        let diff = delta_e_lab(*lab, target).powi(2);
+        if diff < best_diff {
+            best_diff = diff;
+            best_idx = i;
+        }
+    }
+
+    best_idx
+}
+
+#[inline]
+fn nearest_lab_scaler(labs: &[[f32; 3]], target: [f32; 3]) -> usize {
+    let mut best_idx = 0;
+    let mut best_diff = f32::INFINITY;
+
+    for (i, &lab) in labs.iter().enumerate() {
+        let diff = delta_e_lab(lab, target);
+        if diff < best_diff {
+            best_diff = diff;
+            best_idx = i;
+        }
+    }
+
+    best_idx
+}
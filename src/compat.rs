@@ -0,0 +1,150 @@
+use oxocarbon_utils::{midpoint_hex, parse_hex_rgba_u8 as parse_hex_color};
+
+/// a color relative to the base at the current depth:
+/// - `#393939` is that gray at the current depth
+/// - `mix(a, b)` is the midpoint of two expressions
+/// - `depth(#131313, #030303)` interpolates between an explicit standard and OLED value
+pub(crate) enum ColorExpr {
+    Gray(String),
+    Mix(Box<ColorExpr>, Box<ColorExpr>),
+    Depth(String, String),
+    /// a color already resolved for a theme, whatever the depth
+    Fixed(String),
+}
+
+impl ColorExpr {
+    fn parse(src: &str) -> Result<Self, String> {
+        let src = src.trim();
+        if let Some(args) = call_args(src, "mix") {
+            let [a, b] = split_args(args)?;
+            return Ok(Self::Mix(
+                Box::new(Self::parse(a)?),
+                Box::new(Self::parse(b)?),
+            ));
+        }
+        if let Some(args) = call_args(src, "depth") {
+            let [standard, oled] = split_args(args)?;
+            return Ok(Self::Depth(hex(standard)?, hex(oled)?));
+        }
+        hex(src).map(Self::Gray)
+    }
+
+    fn eval(&self, depth: f32) -> String {
+        match self {
+            Self::Gray(hex) => crate::depth_shade(hex, depth),
+            Self::Mix(a, b) => midpoint_hex(&a.eval(depth), &b.eval(depth)),
+            Self::Depth(standard, oled) => crate::mix_hex(standard, oled, depth),
+            Self::Fixed(hex) => hex.clone(),
+        }
+    }
+
+    // the standard-depth color with every gray passed through `map`
+    fn remap(&self, map: &dyn Fn(&str) -> String) -> String {
+        match self {
+            Self::Gray(hex) | Self::Depth(hex, _) => map(hex),
+            Self::Mix(a, b) => midpoint_hex(&a.remap(map), &b.remap(map)),
+            Self::Fixed(hex) => hex.clone(),
+        }
+    }
+}
+
+/// one `[compat.<group>]` table: every key in `keys` gets `color`
+pub(crate) struct CompatGroup {
+    color: ColorExpr,
+    keys: Vec<String>,
+}
+
+impl CompatGroup {
+    /// the group with its grays moved onto another theme's ladder by `map`
+    pub(crate) fn remap(&self, map: &dyn Fn(&str) -> String) -> Self {
+        Self {
+            color: ColorExpr::Fixed(self.color.remap(map)),
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+/// the `[compat]` groups of oxocarbon.toml, for themes that bring none
+pub(crate) fn builtin_groups() -> Vec<CompatGroup> {
+    let manifest: toml::Table =
+        toml::from_str(include_str!("../oxocarbon.toml")).expect("oxocarbon.toml parses");
+    parse_compat(&manifest["compat"]).expect("built-in compat groups are valid")
+}
+
+/// reads the manifest's `[compat]` groups. a key assigned by two groups is an error rather
+/// than whichever group happens to run last
+pub(crate) fn parse_compat(table: &toml::Value) -> Result<Vec<CompatGroup>, String> {
+    let groups = table.as_table().ok_or("`compat` must be a table")?;
+    let mut owners: Vec<(&str, &str)> = Vec::new();
+    let mut out = Vec::with_capacity(groups.len());
+    for (name, group) in groups {
+        let color = group
+            .get("color")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("`compat.{name}` needs a `color` expression"))?;
+        let color = ColorExpr::parse(color).map_err(|e| format!("`compat.{name}.color`: {e}"))?;
+        let keys = group
+            .get("keys")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| format!("`compat.{name}` needs a `keys` list"))?;
+        let mut group_keys = Vec::with_capacity(keys.len());
+        for key in keys {
+            let key = key
+                .as_str()
+                .ok_or_else(|| format!("`compat.{name}.keys` must be strings"))?;
+            if let Some((_, other)) = owners.iter().find(|(k, _)| *k == key) {
+                return Err(format!(
+                    "`{key}` is assigned by both `compat.{other}` and `compat.{name}`"
+                ));
+            }
+            owners.push((key, name));
+            group_keys.push(key.to_string());
+        }
+        out.push(CompatGroup {
+            color,
+            keys: group_keys,
+        });
+    }
+    Ok(out)
+}
+
+/// compatibility variants: contrast panels, gutter and borders from the manifest groups
+pub(crate) fn apply_compat(colors: &mut toml::value::Table, groups: &[CompatGroup], depth: f32) {
+    for group in groups {
+        let color = toml::Value::String(group.color.eval(depth));
+        for key in &group.keys {
+            colors.insert(key.clone(), color.clone());
+        }
+    }
+}
+
+// `mix(a, b)` -> `a, b`
+fn call_args<'a>(src: &'a str, name: &str) -> Option<&'a str> {
+    src.strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+// splits two arguments on the comma outside any nested call
+fn split_args(args: &str) -> Result<[&str; 2], String> {
+    let mut nesting = 0usize;
+    for (i, ch) in args.char_indices() {
+        match ch {
+            '(' => nesting += 1,
+            ')' => nesting = nesting.saturating_sub(1),
+            ',' if nesting == 0 => {
+                return Ok([&args[..i], &args[i + 1..]]);
+            }
+            _ => {}
+        }
+    }
+    Err(format!("expected two arguments in `({args})`"))
+}
+
+fn hex(src: &str) -> Result<String, String> {
+    let src = src.trim();
+    parse_hex_color(src)
+        .map(|_| src.to_ascii_lowercase())
+        .ok_or_else(|| format!("`{src}` is not a hex color"))
+}
@@ -0,0 +1,222 @@
+use oxocarbon_utils::{
+    composite_over, contrast_ratio, ensure_contrast, format_hex_color,
+    parse_hex_rgba_u8 as parse_hex_color,
+};
+
+// wcag aaa for body text
+const HC_MIN_CONTRAST: f32 = 7.0;
+// wcag aa for body text
+pub(crate) const AA_MIN_CONTRAST: f32 = 4.5;
+
+// contrast borders, picked from the accent family then raised like any foreground
+const HC_BORDERS: [(&str, &[&str]); 2] = [
+    (
+        "contrastActiveBorder",
+        &["tab.activeBorderTop", "focusBorder"],
+    ),
+    (
+        "contrastBorder",
+        &["textLink.activeForeground", "textLink.foreground"],
+    ),
+];
+
+/// high contrast transform: opaque backgrounds, contrast borders, foregrounds >= 7:1.
+/// a foreground neither white nor black can raise moves its paired surface too, one that
+/// has no surface of its own is an error naming it
+pub(crate) fn apply_high_contrast(value: &mut toml::Value) -> Result<(), String> {
+    let root = value.as_table_mut().expect("root must be a table");
+    let is_light = root
+        .get("type")
+        .and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case("light"));
+    root.insert(
+        "type".into(),
+        toml::Value::String(if is_light { "hcLight" } else { "hc" }.into()),
+    );
+
+    let Some(colors) = root.get_mut("colors").and_then(|v| v.as_table_mut()) else {
+        return Ok(());
+    };
+    let base = base_background(colors, is_light);
+
+    // flatten translucent surfaces and text so nothing underneath bleeds through
+    for (key, v) in colors.iter_mut() {
+        if !(is_key_kind(key, "background") || is_key_kind(key, "foreground")) {
+            continue;
+        }
+        if let toml::Value::String(s) = v
+            && let Some((rgb, Some(a))) = parse_hex_color(s)
+        {
+            *s = format_hex_color(composite_over(rgb, Some(a), base), None);
+        }
+    }
+
+    for (key, candidates) in HC_BORDERS {
+        if let Some(rgb) = candidates.iter().find_map(|k| {
+            colors
+                .get(*k)
+                .and_then(|v| v.as_str())
+                .and_then(parse_hex_color)
+                .map(|(rgb, _)| rgb)
+        }) {
+            let rgb = ensure_contrast(rgb, base, HC_MIN_CONTRAST);
+            colors.insert(key.into(), toml::Value::String(format_hex_color(rgb, None)));
+        }
+    }
+
+    // raise foregrounds against their paired surface when one exists
+    for key in foreground_keys(colors) {
+        let bg = surface_for(colors, &key, base);
+        let Some(toml::Value::String(s)) = colors.get_mut(&key) else {
+            continue;
+        };
+        raise_hex(s, bg, HC_MIN_CONTRAST);
+        let Some((fg, _)) = parse_hex_color(s) else {
+            continue;
+        };
+        if contrast_ratio(fg, bg) >= HC_MIN_CONTRAST {
+            continue;
+        }
+        // a mid-tone surface, push it away from the best extreme instead
+        let surface = paired_background(&key)
+            .filter(|k| colors.contains_key(k))
+            .ok_or_else(|| unreachable_contrast(&key))?;
+        let moved = ensure_contrast(bg, fg, HC_MIN_CONTRAST);
+        if contrast_ratio(fg, moved) < HC_MIN_CONTRAST {
+            return Err(unreachable_contrast(&key));
+        }
+        colors.insert(surface, toml::Value::String(format_hex_color(moved, None)));
+    }
+
+    let Some(tokens) = root.get_mut("tokenColors").and_then(|v| v.as_array_mut()) else {
+        return Ok(());
+    };
+    for item in tokens {
+        if let Some(toml::Value::String(s)) = token_foreground_mut(item) {
+            raise_hex(s, base, HC_MIN_CONTRAST);
+            if parse_hex_color(s).is_some_and(|(fg, _)| contrast_ratio(fg, base) < HC_MIN_CONTRAST)
+            {
+                let scope = item
+                    .get("scope")
+                    .map_or_else(String::new, ToString::to_string);
+                return Err(unreachable_contrast(&format!("tokenColors {scope}")));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unreachable_contrast(key: &str) -> String {
+    format!("`{key}` can't reach {HC_MIN_CONTRAST}:1 against its background")
+}
+
+/// re-runs the contrast checks after a color transform: every foreground that lost contrast
+/// against its surface relative to `reference` gets it back, up to `min`
+pub(crate) fn restore_contrast(value: &mut toml::Value, reference: &toml::Value, min: f32) {
+    let is_light = value
+        .get("type")
+        .and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case("light"));
+    let (Some(colors), Some(ref_colors)) = (
+        value.get_mut("colors").and_then(|v| v.as_table_mut()),
+        reference.get("colors").and_then(|v| v.as_table()),
+    ) else {
+        return;
+    };
+    let base = base_background(colors, is_light);
+    let ref_base = base_background(ref_colors, is_light);
+
+    for key in foreground_keys(colors) {
+        let bg = surface_for(colors, &key, base);
+        let ref_bg = surface_for(ref_colors, &key, ref_base);
+        let Some(target) = ref_colors
+            .get(&key)
+            .and_then(|v| v.as_str())
+            .and_then(parse_hex_color)
+            .map(|(rgb, a)| contrast_ratio(composite_over(rgb, a, ref_bg), ref_bg).min(min))
+        else {
+            continue;
+        };
+        if let Some(toml::Value::String(s)) = colors.get_mut(&key) {
+            raise_hex(s, bg, target);
+        }
+    }
+
+    let (Some(tokens), Some(ref_tokens)) = (
+        value.get_mut("tokenColors").and_then(|v| v.as_array_mut()),
+        reference.get("tokenColors").and_then(|v| v.as_array()),
+    ) else {
+        return;
+    };
+    for (item, ref_item) in tokens.iter_mut().zip(ref_tokens) {
+        let Some(target) = ref_item
+            .get("settings")
+            .and_then(|v| v.get("foreground"))
+            .and_then(|v| v.as_str())
+            .and_then(parse_hex_color)
+            .map(|(rgb, a)| contrast_ratio(composite_over(rgb, a, ref_base), ref_base).min(min))
+        else {
+            continue;
+        };
+        if let Some(toml::Value::String(s)) = token_foreground_mut(item) {
+            raise_hex(s, base, target);
+        }
+    }
+}
+
+fn base_background(colors: &toml::value::Table, is_light: bool) -> [u8; 3] {
+    colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex_color)
+        .map_or(if is_light { [0xff; 3] } else { [0x16; 3] }, |(rgb, _)| rgb)
+}
+
+fn foreground_keys(colors: &toml::value::Table) -> Vec<String> {
+    colors
+        .keys()
+        .filter(|k| is_key_kind(k, "foreground"))
+        .cloned()
+        .collect()
+}
+
+fn surface_for(colors: &toml::value::Table, key: &str, base: [u8; 3]) -> [u8; 3] {
+    paired_background(key)
+        .and_then(|k| colors.get(&k))
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex_color)
+        .map_or(base, |(rgb, a)| composite_over(rgb, a, base))
+}
+
+fn token_foreground_mut(item: &mut toml::Value) -> Option<&mut toml::Value> {
+    item.get_mut("settings")
+        .and_then(|v| v.get_mut("foreground"))
+}
+
+fn raise_hex(s: &mut String, bg: [u8; 3], min: f32) {
+    if let Some((rgb, alpha)) = parse_hex_color(s) {
+        let flat = composite_over(rgb, alpha, bg);
+        if contrast_ratio(flat, bg) >= min {
+            return;
+        }
+        *s = format_hex_color(ensure_contrast(flat, bg, min), None);
+    }
+}
+
+#[inline]
+fn is_key_kind(key: &str, kind: &str) -> bool {
+    let tail = key.rsplit('.').next().unwrap_or(key);
+    tail.len() >= kind.len() && tail[tail.len() - kind.len()..].eq_ignore_ascii_case(kind)
+}
+
+// `tab.activeForeground` -> `tab.activeBackground`, `button.foreground` -> `button.background`
+fn paired_background(key: &str) -> Option<String> {
+    let (prefix, tail) = key.rsplit_once('.')?;
+    let stem = &tail[..tail.len() - "foreground".len()];
+    let bg = if stem.is_empty() {
+        "background"
+    } else {
+        "Background"
+    };
+    Some(format!("{prefix}.{stem}{bg}"))
+}
@@ -0,0 +1,113 @@
+use oxocarbon_utils::{format_hex_color, luminance_from_u8, parse_hex_rgba_u8 as parse_hex_color};
+
+use crate::ramp::MonoRamp;
+use crate::style::{scope_matches, scopes};
+
+// how far a muted foreground keeps from the background, as a share of its luminance distance
+const FOCUS_DIM: f32 = 0.5;
+
+/// the manifest's `[focus]` table: the longest matching selector across both lists decides,
+/// scopes matching neither are muted
+#[derive(Default)]
+pub(crate) struct FocusScopes {
+    emphasis: Vec<String>,
+    muted: Vec<String>,
+}
+
+impl FocusScopes {
+    pub(crate) fn from_manifest(table: &toml::Value) -> Self {
+        let list = |key: &str| -> Vec<String> {
+            table
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            emphasis: list("emphasis"),
+            muted: list("muted"),
+        }
+    }
+
+    fn is_emphasised(&self, scope: &str) -> bool {
+        let longest = |sels: &[String]| {
+            sels.iter()
+                .filter(|sel| scope_matches(scope, sel))
+                .map(String::len)
+                .max()
+        };
+        match (longest(&self.emphasis), longest(&self.muted)) {
+            (Some(e), Some(m)) => e > m,
+            (e, _) => e.is_some(),
+        }
+    }
+}
+
+/// focus transform: token rules outside the emphasised scopes fade to low-contrast grays
+/// from `ramp`, rules mixing both kinds of scope are split so each keeps its own color
+pub(crate) fn apply_focus(value: &mut toml::Value, focus: &FocusScopes, ramp: &MonoRamp) {
+    let bg_y = value
+        .get("colors")
+        .and_then(|c| c.get("editor.background"))
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex_color)
+        .map_or(0.0, |(rgb, _)| luminance_from_u8(rgb[0], rgb[1], rgb[2]));
+    let Some(tokens) = value.get_mut("tokenColors").and_then(|v| v.as_array_mut()) else {
+        return;
+    };
+
+    let mut out = Vec::with_capacity(tokens.len());
+    for item in tokens.drain(..) {
+        let Some(muted_fg) = item
+            .get("settings")
+            .and_then(|v| v.get("foreground"))
+            .and_then(|v| v.as_str())
+            .and_then(|fg| mute_hex(fg, bg_y, ramp))
+        else {
+            out.push(item);
+            continue;
+        };
+
+        let (emphasised, muted): (Vec<String>, Vec<String>) = scopes(&item)
+            .into_iter()
+            .partition(|s| focus.is_emphasised(s));
+        if muted.is_empty() {
+            out.push(item);
+            continue;
+        }
+        let mut item = if emphasised.is_empty() {
+            item
+        } else {
+            out.push(with_scopes(item.clone(), emphasised));
+            with_scopes(item, muted)
+        };
+        if let Some(settings) = item.get_mut("settings").and_then(|v| v.as_table_mut()) {
+            settings.insert("foreground".into(), toml::Value::String(muted_fg));
+        }
+        out.push(item);
+    }
+    *tokens = out;
+}
+
+// nearest ramp gray to a luminance pulled toward the background
+fn mute_hex(hex: &str, bg_y: f32, ramp: &MonoRamp) -> Option<String> {
+    let (rgb, alpha) = parse_hex_color(hex)?;
+    let y = luminance_from_u8(rgb[0], rgb[1], rgb[2]);
+    let pick = ramp.nearest_rgb(bg_y + (y - bg_y) * FOCUS_DIM);
+    Some(format_hex_color(pick, alpha))
+}
+
+fn with_scopes(mut item: toml::Value, scopes: Vec<String>) -> toml::Value {
+    if let Some(table) = item.as_table_mut() {
+        table.insert(
+            "scope".into(),
+            toml::Value::Array(scopes.into_iter().map(toml::Value::String).collect()),
+        );
+    }
+    item
+}
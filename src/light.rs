@@ -0,0 +1,95 @@
+use oxocarbon_utils::{format_hex_color, pack_rgb, parse_hex_rgba_u8 as parse_hex_color};
+
+// role-based G100 -> G10 mapping (Carbon White/G10 tokens)
+// one pass per color, so a target never gets remapped by a later entry
+const LIGHT_GRAYS: [(&str, &str); 18] = [
+    // surfaces
+    ("#161616", "#f4f4f4"), // background
+    ("#131313", "#ffffff"), // compat gutter, sits below background
+    ("#181818", "#ffffff"), // layer-01
+    ("#1b1b1b", "#ffffff"), // layer-01, raised widgets
+    ("#212121", "#ffffff"), // layer-01, peek results / odd rows
+    ("#1e1e1e", "#e8e8e8"), // layer-hover-01, compat panels
+    ("#262626", "#e0e0e0"), // layer-02, border-subtle
+    ("#2f2f2f", "#d1d1d1"), // layer-selected-hover, compat borders
+    ("#363636", "#c6c6c6"), // drop targets
+    ("#393939", "#c6c6c6"), // layer-selected, selection
+    ("#525252", "#a8a8a8"), // border-strong, line numbers
+    // text
+    ("#6f6f6f", "#6f6f6f"), // text-helper, comments
+    ("#8d8d8d", "#6f6f6f"), // text-disabled, inactive labels
+    ("#ada8a8", "#6f6f6f"), // inlay hints
+    ("#c1c7cd", "#525252"), // text-secondary
+    ("#dde1e6", "#393939"), // text-secondary, emphasised
+    ("#f2f4f8", "#161616"), // text-primary
+    ("#ffffff", "#000000"), // text-primary, bold
+];
+
+// accents re-toned to Carbon 60 for legibility on white, the deeper shade of each
+// pair steps one further so they stay distinct
+const LIGHT_ACCENTS: [(&str, &str); 12] = [
+    ("#ff7eb6", "#d02670"), // magenta 40 -> 60
+    ("#ee5396", "#9f1853"), // magenta 50 -> 70
+    ("#be95ff", "#8a3ffc"), // purple 40 -> 60
+    ("#a6c8ff", "#0f62fe"), // blue 30 -> 60
+    ("#78a9ff", "#0043ce"), // blue 40 -> 70
+    ("#0f62fe", "#0f62fe"), // blue 60
+    ("#82cfff", "#0072c3"), // cyan 30 -> 60
+    ("#33b1ff", "#00539a"), // cyan 40 -> 70
+    ("#3ddbd9", "#007d79"), // teal 30 -> 60
+    ("#08bdba", "#005d5d"), // teal 40 -> 70
+    ("#42be65", "#198038"), // green 40 -> 60
+    ("#f1c21b", "#8e6a00"), // yellow 30 -> 60
+];
+
+// text drawn on a solid accent stays white
+const LIGHT_ON_ACCENT_KEYS: [&str; 7] = [
+    "statusBar.debuggingForeground",
+    "statusBar.noFolderForeground",
+    "statusBarItem.remoteForeground",
+    "extensionBadge.remoteForeground",
+    "activityErrorBadge.foreground",
+    "activityWarningBadge.foreground",
+    "scmGraph.historyItemHoverLabelForeground",
+];
+
+const ON_ACCENT: &str = "#ffffff";
+
+/// maps a dark-theme color to its light counterpart, keeping alpha
+pub(crate) fn map_hex(s: &str) -> Option<String> {
+    let (rgb, alpha) = parse_hex_color(s)?;
+    let packed = pack_rgb(rgb);
+    LIGHT_GRAYS
+        .iter()
+        .chain(LIGHT_ACCENTS.iter())
+        .find(|(from, _)| parse_hex_color(from).is_some_and(|(f, _)| pack_rgb(f) == packed))
+        .and_then(|&(_, to)| parse_hex_color(to))
+        .map(|(to, _)| format_hex_color(to, alpha))
+}
+
+/// curated light variant: G10 surfaces, 60-level accents, `type = "light"`
+pub(crate) fn apply_light(value: &mut toml::Value) {
+    crate::walk_value_strings_mut(value, &mut |s: &mut String| {
+        if let Some(mapped) = map_hex(s) {
+            *s = mapped;
+        }
+    });
+
+    let root = value.as_table_mut().expect("root must be a table");
+    root.insert("type".into(), toml::Value::String("light".into()));
+    if let Some(colors) = root.get_mut("colors").and_then(|v| v.as_table_mut()) {
+        for key in LIGHT_ON_ACCENT_KEYS {
+            if colors.contains_key(key) {
+                colors.insert(key.into(), toml::Value::String(ON_ACCENT.into()));
+            }
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn is_light_accent(rgb: [u8; 3]) -> bool {
+    let packed = pack_rgb(rgb);
+    LIGHT_ACCENTS
+        .iter()
+        .any(|(_, to)| parse_hex_color(to).is_some_and(|(t, _)| pack_rgb(t) == packed))
+}
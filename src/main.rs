@@ -4,27 +4,55 @@
 
 #![warn(clippy::pedantic)]
 
+mod compat;
+mod contrast;
+mod focus;
+mod light;
+mod manifest;
+mod palette;
 mod ramp;
-
+mod style;
+mod vscode;
+
+use compat::apply_compat;
+use contrast::{AA_MIN_CONTRAST, apply_high_contrast, restore_contrast};
+use focus::{FocusScopes, apply_focus};
+use light::apply_light;
+use manifest::Manifest;
 use oxocarbon_utils::{
-    format_hex_color, luminance_from_u8, midpoint_hex, parse_hex_rgba_u8 as parse_hex_color,
+    format_hex_color, luminance_from_u8, mix_oklab, parse_hex_rgba_u8 as parse_hex_color,
+    rgb_to_oklch,
 };
+use palette::{ACCENTS, NEUTRAL_CHROMA, apply_chroma, apply_seed, apply_white_point};
 use ramp::{MonoRamp, is_monochrome_candidate, select_monochrome_ramp};
-use std::{env, fs, io, process};
+use std::{env, fmt::Write as _, fs, io, process};
+use style::{FontStyle, StylePolicy, apply_style_policy};
 
 #[derive(Default)]
 struct Options {
-    flags: u8,
+    flags: u16,
     mono_family: Option<String>,
+    depth: Option<f32>,
+    seed: Option<[u8; 3]>,
+    chroma: Option<f32>,
+    white_point: Option<f32>,
+    italic_scopes: Option<Vec<String>>,
+    overlays: Vec<String>,
+    json_input: Option<bool>,
     input_src: String,
 }
 
 impl Options {
-    const PRETTY: u8 = 1 << 0;
-    const OLED: u8 = 1 << 1;
-    const MONOCHROME: u8 = 1 << 3;
-    const COMPAT: u8 = 1 << 2;
-    const PRINT: u8 = 1 << 4;
+    const PRETTY: u16 = 1 << 0;
+    const OLED: u16 = 1 << 1;
+    const MONOCHROME: u16 = 1 << 3;
+    const COMPAT: u16 = 1 << 2;
+    const PRINT: u16 = 1 << 4;
+    const HIGH_CONTRAST: u16 = 1 << 5;
+    const LIGHT: u16 = 1 << 6;
+    const NO_ITALIC: u16 = 1 << 7;
+    const NO_BOLD: u16 = 1 << 8;
+    const FOCUS: u16 = 1 << 9;
     fn from_env_args() -> Self {
         let mut args = env::args().skip(1);
         let mut opts = Options {
@@ -33,21 +61,83 @@ impl Options {
         };
 
         while let Some(arg) = args.next() {
+            if let Some(flag) = Self::flag(&arg) {
+                opts.flags |= flag;
+                continue;
+            }
             match arg.as_str() {
-                "-p" | "--pretty" => opts.flags |= Self::PRETTY,
-                "--oled" => opts.flags |= Self::OLED,
-                "-m" | "--mono" | "--monochrome" => opts.flags |= Self::MONOCHROME,
-                "-c" | "--compat" | "--compatibility" => opts.flags |= Self::COMPAT,
-                "--print" => opts.flags |= Self::PRINT,
                 "--mono-family" | "--monochrome-family" => {
-                    if let Some(fam) = args.next() {
-                        opts.mono_family = Some(fam.to_lowercase());
-                    } else {
-                        eprintln!(
-                            "Expected a value after --mono-family, e.g. gray|coolgray|warmgray"
-                        );
-                        process::exit(2);
-                    }
+                    let fam = arg_value(
+                        args.next(),
+                        Some,
+                        "a value after --mono-family, e.g. gray|coolgray|warmgray",
+                    );
+                    opts.mono_family = Some(fam.to_lowercase());
+                }
+                "--depth" => {
+                    let depth = arg_value(
+                        args.next(),
+                        |v| v.parse::<f32>().ok().filter(|d| (0.0..=1.0).contains(d)),
+                        "a value between 0.0 (standard) and 1.0 (OLED) after --depth",
+                    );
+                    opts.depth = Some(depth);
+                }
+                "--seed" => {
+                    let (rgb, _) = arg_value(
+                        args.next(),
+                        |v| parse_hex_color(&v),
+                        "a hex color after --seed, e.g. #33b1ff",
+                    );
+                    opts.seed = Some(rgb);
+                }
+                "--chroma" => {
+                    let factor = arg_value(
+                        args.next(),
+                        |v| v.parse::<f32>().ok().filter(|f| (0.0..=1.0).contains(f)),
+                        "a chroma factor between 0.0 and 1.0 after --chroma",
+                    );
+                    opts.chroma = Some(factor);
+                }
+                "--white-point" => {
+                    let kelvin = arg_value(
+                        args.next(),
+                        |v| {
+                            v.trim_end_matches(['K', 'k'])
+                                .parse::<f32>()
+                                .ok()
+                                .filter(|k| (1667.0..=25000.0).contains(k))
+                        },
+                        "a color temperature (1667..25000 K) after --white-point",
+                    );
+                    opts.white_point = Some(kelvin);
+                }
+                "--italic-scopes" => {
+                    let scopes = arg_value(
+                        args.next(),
+                        |v| parse_scope_list(&v),
+                        "a list of scopes after --italic-scopes, e.g. comment,keyword",
+                    );
+                    opts.italic_scopes = Some(scopes);
+                }
+                "--input-format" => {
+                    let json = arg_value(
+                        args.next(),
+                        |v| match v.to_ascii_lowercase().as_str() {
+                            "toml" => Some(false),
+                            "json" | "jsonc" => Some(true),
+                            _ => None,
+                        },
+                        "an input format after --input-format, e.g. toml|json|jsonc",
+                    );
+                    opts.json_input = Some(json);
+                }
+                "--overlay" => {
+                    let path = arg_value(
+                        args.next(),
+                        Some,
+                        "a manifest path after --overlay, e.g. team.toml",
+                    );
+                    opts.overlays.push(path);
                 }
                 other if opts.input_src == "-" => {
                     opts.input_src = other.to_string();
@@ -56,9 +146,60 @@ impl Options {
             }
         }
 
+        // VS Code theme JSON unless told otherwise by --input-format
+        opts.json_input
+            .get_or_insert_with(|| is_json_path(&opts.input_src));
+
+        opts.check_conflicts();
         opts
     }
 
+    // switches that take no value
+    fn flag(arg: &str) -> Option<u16> {
+        Some(match arg {
+            "-p" | "--pretty" => Self::PRETTY,
+            "--oled" => Self::OLED,
+            "-m" | "--mono" | "--monochrome" => Self::MONOCHROME,
+            "-c" | "--compat" | "--compatibility" => Self::COMPAT,
+            "--print" => Self::PRINT,
+            "--hc" | "--high-contrast" => Self::HIGH_CONTRAST,
+            "--light" => Self::LIGHT,
+            "--no-italic" => Self::NO_ITALIC,
+            "--no-bold" => Self::NO_BOLD,
+            "--focus" => Self::FOCUS,
+            _ => return None,
+        })
+    }
+
+    // exits on flag combinations that have no meaningful output
+    fn check_conflicts(&self) {
+        if self.is_light() && (self.is_oled() || self.is_print() || self.depth.is_some()) {
+            eprintln!(
+                "--light combines with --compat and --monochrome, not --oled, --depth or --print"
+            );
+            process::exit(2);
+        }
+        if self.seed.is_some() && self.is_monochrome() {
+            eprintln!("--seed has no accents to rotate in --monochrome");
+            process::exit(2);
+        }
+        if self.is_no_italic() && self.italic_scopes.is_some() {
+            eprintln!("--italic-scopes has nothing to italicize with --no-italic");
+            process::exit(2);
+        }
+        if self.is_json_input() && (self.is_light() || self.seed.is_some() || self.depth.is_some())
+        {
+            eprintln!(
+                "--light, --seed and --depth follow oxocarbon's own palette, not a VS Code theme"
+            );
+            process::exit(2);
+        }
+        if self.is_oled() && self.depth.is_some() {
+            eprintln!("--oled is --depth 1.0, pass one or the other");
+            process::exit(2);
+        }
+    }
+
     #[inline]
     fn is_pretty(&self) -> bool {
         self.flags & Self::PRETTY != 0
@@ -79,98 +220,289 @@ impl Options {
     fn is_print(&self) -> bool {
         self.flags & Self::PRINT != 0
     }
+    #[inline]
+    fn is_high_contrast(&self) -> bool {
+        self.flags & Self::HIGH_CONTRAST != 0
+    }
+    #[inline]
+    fn is_light(&self) -> bool {
+        self.flags & Self::LIGHT != 0
+    }
+    #[inline]
+    fn is_json_input(&self) -> bool {
+        self.json_input == Some(true)
+    }
+    #[inline]
+    fn is_focus(&self) -> bool {
+        self.flags & Self::FOCUS != 0
+    }
+    #[inline]
+    fn is_no_italic(&self) -> bool {
+        self.flags & Self::NO_ITALIC != 0
+    }
+    #[inline]
+    fn is_no_bold(&self) -> bool {
+        self.flags & Self::NO_BOLD != 0
+    }
+    /// background depth, 0.0 is standard and 1.0 is OLED
+    #[inline]
+    fn depth(&self) -> f32 {
+        self.depth.unwrap_or(if self.is_oled() { 1.0 } else { 0.0 })
+    }
+}
+
+// `comment, keyword` -> ["comment", "keyword"], none for an empty list
+fn parse_scope_list(v: &str) -> Option<Vec<String>> {
+    let scopes: Vec<String> = v
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+    (!scopes.is_empty()).then_some(scopes)
+}
+
+fn is_json_path(path: &str) -> bool {
+    path.rsplit_once('.').is_some_and(|(_, ext)| {
+        ext.eq_ignore_ascii_case("json") || ext.eq_ignore_ascii_case("jsonc")
+    })
+}
+
+// parses a flag's value, exiting with usage status when it's missing or malformed
+fn arg_value<T>(
+    value: Option<String>,
+    parse: impl FnOnce(String) -> Option<T>,
+    expected: &str,
+) -> T {
+    value.and_then(parse).unwrap_or_else(|| {
+        eprintln!("Expected {expected}");
+        process::exit(2);
+    })
 }
 
 fn main() {
     let opts = Options::from_env_args();
-    let toml_buf = read_input(&opts.input_src);
 
-    // parse once, mutate, emit JSON
-    let mut value: toml::Value = toml::from_str(&toml_buf).unwrap_or_else(|e| {
-        eprintln!("TOML parse error ({}): {e}", opts.input_src);
+    // parse once, following `include`, mutate, emit JSON
+    let mut base = if opts.is_json_input() {
+        vscode::load(&opts.input_src)
+    } else {
+        manifest::load(&opts.input_src)
+    };
+    // overlays land on the base before any variant transform, in the order given
+    for overlay in &opts.overlays {
+        manifest::merge_theme(&mut base, manifest::load(overlay));
+    }
+    let mut value = toml::Value::Table(base);
+
+    let manifest = Manifest::take(&mut value, &opts.input_src);
+    manifest.check_variants(opts.is_focus(), &opts.input_src);
+
+    apply_palette_variants(&mut value, &opts, &manifest);
+    apply_finishing_variants(&mut value, &opts, &manifest);
+
+    if let Err(e) = (if opts.is_pretty() {
+        serde_json::to_writer_pretty
+    } else {
+        serde_json::to_writer
+    })(io::stdout().lock(), &value)
+    {
+        eprintln!("Failed to write JSON: {e}");
         process::exit(1);
-    });
+    }
+}
+
+// variants that pick the palette: depth, compat, light, accents, monochrome and focus
+fn apply_palette_variants(value: &mut toml::Value, opts: &Options, manifest: &Manifest) {
+    let overrides =
+        |value: &mut toml::Value, tag| manifest.apply_overrides(value, tag, opts.is_light());
+
+    // a theme without compat groups gets the built-in ones on its own gray ladder, which the
+    // OLED step is about to move
+    let derived_compat = (opts.is_compat() && manifest.compat.is_empty())
+        .then(|| value.get("colors").and_then(|v| v.as_table()))
+        .flatten()
+        .map(|colors| vscode::compat_groups(colors, opts.is_oled()));
+
+    // apply OLED replacements first, or the interpolated grays for a custom depth
+    if let Some(colors) = colors_table_mut(value) {
+        if opts.is_oled() && opts.is_json_input() {
+            let steps = vscode::oled_replacements(colors);
+            let steps: Vec<(&str, &str)> = steps
+                .iter()
+                .map(|(f, t)| (f.as_str(), t.as_str()))
+                .collect();
+            apply_replacements_in_table(colors, &steps);
+        } else if opts.is_oled() {
+            apply_replacements_in_table(colors, &OLED_REPLACEMENTS);
+        } else if let Some(depth) = opts.depth {
+            let shades: Vec<(&str, String)> = OLED_REPLACEMENTS
+                .iter()
+                .map(|&(from, _)| (from, depth_shade(from, depth)))
+                .collect();
+            let shades: Vec<(&str, &str)> = shades.iter().map(|(f, t)| (*f, t.as_str())).collect();
+            apply_replacements_in_table(colors, &shades);
+        }
+    }
+    if opts.is_oled() {
+        overrides(value, "oled");
+    }
 
-    // apply OLED replacements first
-    if opts.is_oled()
-        && let Some(colors) = colors_table_mut(&mut value)
+    // compatibility adjustments, relative to the base at the current depth
+    if opts.is_compat()
+        && let Some(colors) = colors_table_mut(value)
     {
-        apply_replacements_in_table(colors, &OLED_REPLACEMENTS);
+        let groups = derived_compat.as_deref().unwrap_or(&manifest.compat);
+        apply_compat(colors, groups, opts.depth());
+    }
+    if opts.is_compat() {
+        overrides(value, "compat");
+    }
+
+    // light variant: G10 surfaces and 60-level accents, after compat so its grays map too
+    if opts.is_light() {
+        apply_light(value);
+        overrides(value, "light");
+    }
+
+    // seeded accents, rotated from whichever family the variant ended up with
+    if let Some(seed) = opts.seed {
+        let family: Vec<String> = ACCENTS
+            .iter()
+            .map(|&hex| {
+                opts.is_light()
+                    .then(|| light::map_hex(hex))
+                    .flatten()
+                    .unwrap_or_else(|| hex.to_string())
+            })
+            .collect();
+        apply_seed(value, &family, seed);
     }
 
     // monochrome transform
     if opts.is_monochrome() {
         let family = opts.mono_family.as_deref().unwrap_or("gray");
         let ramp = select_monochrome_ramp(family);
-        apply_monochrome(&mut value, ramp, opts.is_print());
-        // enforce style-based foregrounds for monochrome variants
-        apply_monochrome_style_overrides(&mut value);
+        // a foreign theme has no known accent family, so every chromatic color is one
+        let is_candidate = |rgb: [u8; 3]| {
+            if opts.is_json_input() {
+                rgb_to_oklch(rgb)[1] >= NEUTRAL_CHROMA
+            } else {
+                is_monochrome_candidate(rgb, opts.is_print())
+                    || opts.is_light() && light::is_light_accent(rgb)
+            }
+        };
+        apply_monochrome(value, ramp, is_candidate);
     }
 
-    // compatibility adjustments
-    if opts.is_compat()
-        && let Some(colors) = colors_table_mut(&mut value)
-    {
-        // compatibility variants - contrast panels
-        // - Standard compat: midpoint(#161616, #262626) = #1e1e1e
-        // - OLED compat:     midpoint(#000000, #161616) = #0b0b0b
-        #[rustfmt::skip]
-        let (from, to) = if opts.is_oled() {("#000000", "#161616")} else {("#161616", "#262626")};
-        let c1 = midpoint_hex(from, to);
-        insert_value(colors, &COMPAT_BG_KEYS, &toml::Value::String(c1));
-        // compatibility variants - gutter, six deviations
-        // - Standard compat: #131313
-        // - OLED compat:     #030303
-        let c2 = if opts.is_oled() { "#030303" } else { "#131313" }.to_string();
-        insert_value(colors, &COMPAT_BG_KEYS_2, &toml::Value::String(c2));
-        // compatibility variants - contrast headers, borders
-        // - Standard compat: #393939
-        // - OLED compat:     #262626
-        let c3 = if opts.is_oled() { "#262626" } else { "#393939" }.to_string();
-        #[rustfmt::skip]
-        insert_value(colors, &COMPAT_CONTRAST_KEYS, &toml::Value::String(c3.clone()));
-        // compatibility variants - additional contrast
-        // - Standard compat: midpoint(#161616, contrast_mid_val_1) = #1a1a1a
-        // - OLED compat:     midpoint(#000000, contrast_mid_val_1) = #050505
-        let base = if opts.is_oled() { "#161616" } else { "#262626" };
-        let c4 = midpoint_hex(base, &c3);
-        insert_value(colors, &COMPAT_CONTRAST_KEYS_2, &toml::Value::String(c4));
+    // font-style rules: monochrome foregrounds plus the style policies
+    let policy = style_policy(opts);
+    if !policy.is_empty() {
+        apply_style_policy(value, &policy);
+    }
+    if opts.is_monochrome() {
+        overrides(value, "monochrome");
+    }
+
+    // focus: everything outside the emphasised scopes fades to the gray ramp
+    if opts.is_focus() {
+        let focus = manifest
+            .focus
+            .as_ref()
+            .map(FocusScopes::from_manifest)
+            .unwrap_or_default();
+        let family = opts.mono_family.as_deref().unwrap_or("gray");
+        apply_focus(value, &focus, select_monochrome_ramp(family));
+        overrides(value, "focus");
+    }
+}
+
+// variants that adjust the finished palette, then naming, print and high contrast
+fn apply_finishing_variants(value: &mut toml::Value, opts: &Options, manifest: &Manifest) {
+    let overrides =
+        |value: &mut toml::Value, tag| manifest.apply_overrides(value, tag, opts.is_light());
+
+    // soft variant: scale chroma, then win back any contrast the scaling cost
+    if let Some(factor) = opts.chroma {
+        let reference = value.clone();
+        apply_chroma(value, factor);
+        restore_contrast(value, &reference, AA_MIN_CONTRAST);
+        overrides(value, "soft");
+    }
+
+    // night shift, adapting the finished palette to a lower white point
+    if let Some(kelvin) = opts.white_point {
+        apply_white_point(value, kelvin);
     }
 
     // name override
-    if let Some(name) = compute_theme_name(
-        opts.is_oled(),
-        opts.is_compat(),
-        opts.is_monochrome(),
-        opts.mono_family.as_deref(),
-    ) {
+    // a VS Code theme keeps its own name ahead of the variant tags
+    let base_name = if opts.is_json_input() {
+        value
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Untitled")
+            .to_string()
+    } else {
+        "Oxocarbon".to_string()
+    };
+    if let Some(name) = compute_theme_name(opts, &base_name) {
         value
             .as_table_mut()
             .expect("root must be a table")
             .insert("name".into(), toml::Value::String(name));
     }
+    if opts.is_high_contrast()
+        && let Some(toml::Value::String(name)) = value.get_mut("name")
+    {
+        name.push_str(" (high contrast)");
+    }
 
     // print variant: invert all hex colors and force light type
     if opts.is_print() {
-        invert_all_hex_colors(&mut value);
+        invert_all_hex_colors(value);
         value
             .as_table_mut()
             .unwrap()
             .insert("type".into(), toml::Value::String("light".into()));
+        overrides(value, "print");
     }
 
-    if let Err(e) = (if opts.is_pretty() {
-        serde_json::to_writer_pretty
-    } else {
-        serde_json::to_writer
-    })(io::stdout().lock(), &value)
-    {
-        eprintln!("Failed to write JSON: {e}");
-        process::exit(1);
+    // high contrast runs last so it measures against the final backgrounds
+    if opts.is_high_contrast() {
+        apply_high_contrast(value).unwrap_or_else(|e| {
+            eprintln!("High contrast error: {e}");
+            process::exit(1);
+        });
+        overrides(value, "hc");
+    }
+}
+
+fn style_policy(opts: &Options) -> StylePolicy {
+    let mut policy = StylePolicy::default();
+    // monochrome tells styles apart by color, ahead of any compensation
+    if opts.is_monochrome() {
+        let tone = |hex: &str| {
+            opts.is_light()
+                .then(|| light::map_hex(hex))
+                .flatten()
+                .unwrap_or_else(|| hex.into())
+        };
+        policy.monochrome(tone(ITALIC_FG), tone(BOLD_FG));
+    }
+    if opts.is_no_italic() {
+        policy.without(FontStyle::Italic);
+    }
+    if opts.is_no_bold() {
+        policy.without(FontStyle::Bold);
     }
+    if let Some(scopes) = &opts.italic_scopes {
+        policy.italic_scopes(scopes.clone());
+    }
+    policy
 }
 
-fn read_input(input_src: &str) -> String {
+pub(crate) fn read_input(input_src: &str) -> String {
     fs::read_to_string(input_src).unwrap_or_else(|e| {
         eprintln!("Failed to read '{input_src}': {e}");
         process::exit(1);
@@ -187,51 +519,24 @@ const OLED_REPLACEMENTS: [(&str, &str); 7] = [
     ("#525252", "#393939"),
 ];
 
-const COMPAT_BG_KEYS: [&str; 8] = [
-    "titleBar.activeBackground",
-    "editorGroupHeader.tabsBackground",
-    "tab.inactiveBackground",
-    "activityBar.background",
-    "sideBar.background",
-    "panel.background",
-    "statusBar.background",
-    "editorWidget.background",
-];
-
-#[rustfmt::skip]
-const COMPAT_BG_KEYS_2: [&str; 1] = [
-    "editorGutter.background"
-];
-
-const COMPAT_CONTRAST_KEYS: [&str; 7] = [
-    // borders
-    "titleBar.border",
-    "tab.border",
-    "activityBar.border",
-    "statusBar.border",
-    // additional contrast for readability
-    "titleBar.activeBackground",
-    "list.hoverBackground",
-    "dropdown.background",
-];
+/// a neutral gray at `depth` between its standard and OLED value, mixed in oklab
+pub(crate) fn depth_shade(hex: &str, depth: f32) -> String {
+    match OLED_REPLACEMENTS.iter().find(|(from, _)| *from == hex) {
+        Some(&(from, to)) => mix_hex(from, to, depth),
+        None => hex.to_string(),
+    }
+}
 
-const COMPAT_CONTRAST_KEYS_2: [&str; 4] = [
-    "tab.border",
-    "sideBar.border",
-    "panel.border",
-    "editorWidget.resizeBorder",
-];
+pub(crate) fn mix_hex(a: &str, b: &str, t: f32) -> String {
+    let (a, _) = parse_hex_color(a).expect("invalid hex a");
+    let (b, _) = parse_hex_color(b).expect("invalid hex b");
+    format_hex_color(mix_oklab(a, b, t), None)
+}
 
 fn colors_table_mut(value: &mut toml::Value) -> Option<&mut toml::value::Table> {
     value.get_mut("colors").and_then(|v| v.as_table_mut())
 }
 
-fn insert_value(table: &mut toml::value::Table, keys: &[&str], value: &toml::Value) {
-    for &key in keys {
-        table.insert(key.into(), value.clone());
-    }
-}
-
 fn apply_replacements_in_table(table: &mut toml::value::Table, replacements: &[(&str, &str)]) {
     walk_table_strings_mut(table, &mut |s: &mut String| {
         for &(from, to) in replacements {
@@ -242,43 +547,65 @@ fn apply_replacements_in_table(table: &mut toml::value::Table, replacements: &[(
     });
 }
 
-fn compute_theme_name(
-    oled: bool,
-    compat: bool,
-    monochrome: bool,
-    mono_family: Option<&str>,
-) -> Option<String> {
-    if monochrome {
-        let base = if oled {
-            "Oxocarbon OLED Monochrom"
-        } else {
-            "Oxocarbon Monochrom"
-        };
-        let mut name = match mono_family.unwrap_or("gray") {
-            "coolgray" | "cool-gray" | "cool" => format!("{base} (Cool Gray)"),
-            "warmgray" | "warm-gray" | "warm" => format!("{base} (Warm Gray)"),
-            _ => base.to_string(),
-        };
-        if compat {
-            name.push_str(" (compatibility)");
-        }
-        Some(name)
-    } else {
-        match (oled, compat) {
-            (true, true) => Some("Oxocarbon OLED (compatibility)".to_string()),
-            (true, false) => Some("Oxocarbon OLED".to_string()),
-            (false, true) => Some("Oxocarbon (compatibility)".to_string()),
-            (false, false) => None,
+fn compute_theme_name(opts: &Options, base_name: &str) -> Option<String> {
+    if !(opts.is_oled()
+        || opts.depth.is_some()
+        || opts.seed.is_some()
+        || opts.chroma.is_some()
+        || opts.white_point.is_some()
+        || opts.is_focus()
+        || opts.is_light()
+        || opts.is_compat()
+        || opts.is_monochrome())
+    {
+        return None;
+    }
+    let mut name = String::from(base_name);
+    if opts.is_oled() {
+        name.push_str(" OLED");
+    }
+    if let Some(depth) = opts.depth {
+        let _ = write!(name, " Depth {depth:.2}");
+    }
+    if opts.is_light() {
+        name.push_str(" Light");
+    }
+    if opts.chroma.is_some() {
+        name.push_str(" Soft");
+    }
+    if opts.is_focus() {
+        name.push_str(" Focus");
+    }
+    if let Some(kelvin) = opts.white_point {
+        let _ = write!(name, " Night {kelvin:.0}K");
+    }
+    if opts.is_monochrome() {
+        name.push_str(" Monochrom");
+        match opts.mono_family.as_deref().unwrap_or("gray") {
+            "coolgray" | "cool-gray" | "cool" => name.push_str(" (Cool Gray)"),
+            "warmgray" | "warm-gray" | "warm" => name.push_str(" (Warm Gray)"),
+            _ => {}
         }
     }
+    if let Some(seed) = opts.seed {
+        let _ = write!(name, " Seed {}", format_hex_color(seed, None));
+    }
+    if opts.is_compat() {
+        name.push_str(" (compatibility)");
+    }
+    Some(name)
 }
 
-fn apply_monochrome(value: &mut toml::Value, ramp: &MonoRamp, is_print: bool) {
+fn apply_monochrome(
+    value: &mut toml::Value,
+    ramp: &MonoRamp,
+    is_candidate: impl Fn([u8; 3]) -> bool,
+) {
     walk_value_strings_mut(value, &mut |s: &mut String| {
         let Some((rgb, alpha)) = parse_hex_color(s) else {
             return;
         };
-        if !is_monochrome_candidate(rgb, is_print) {
+        if !is_candidate(rgb) {
             return;
         }
         let y = luminance_from_u8(rgb[0], rgb[1], rgb[2]);
@@ -289,39 +616,10 @@ fn apply_monochrome(value: &mut toml::Value, ramp: &MonoRamp, is_print: bool) {
     });
 }
 
-fn apply_monochrome_style_overrides(value: &mut toml::Value) {
-    let Some(arr) = value.get_mut("tokenColors").and_then(|v| v.as_array_mut()) else {
-        return;
-    };
-    const ITALIC_FG: &str = "#f2f4f8";
-    const BOLD_FG: &str = "#ffffff";
-
-    for item in arr.iter_mut() {
-        let Some(settings) = item.get_mut("settings").and_then(|v| v.as_table_mut()) else {
-            continue;
-        };
-        let Some(font_style) = settings.get("fontStyle").and_then(|v| v.as_str()) else {
-            continue;
-        };
-
-        let has_italic = font_style.contains("italic") || font_style.contains("Italic");
-        let is_bold_only = font_style.trim().eq_ignore_ascii_case("bold");
-
-        if has_italic {
-            settings.insert(
-                "foreground".into(),
-                toml::Value::String(ITALIC_FG.to_string()),
-            );
-        } else if is_bold_only {
-            settings.insert(
-                "foreground".into(),
-                toml::Value::String(BOLD_FG.to_string()),
-            );
-        }
-    }
-}
+const ITALIC_FG: &str = "#f2f4f8";
+const BOLD_FG: &str = "#ffffff";
 
-fn walk_value_strings_mut<F: FnMut(&mut String)>(v: &mut toml::Value, f: &mut F) {
+pub(crate) fn walk_value_strings_mut<F: FnMut(&mut String)>(v: &mut toml::Value, f: &mut F) {
     match v {
         toml::Value::String(s) => f(s),
         toml::Value::Array(a) => a.iter_mut().for_each(|x| walk_value_strings_mut(x, f)),
@@ -332,7 +630,7 @@ fn walk_value_strings_mut<F: FnMut(&mut String)>(v: &mut toml::Value, f: &mut F)
     }
 }
 
-fn walk_table_strings_mut<F: FnMut(&mut String)>(t: &mut toml::value::Table, f: &mut F) {
+pub(crate) fn walk_table_strings_mut<F: FnMut(&mut String)>(t: &mut toml::value::Table, f: &mut F) {
     for (_k, v) in t.iter_mut() {
         walk_value_strings_mut(v, f);
     }
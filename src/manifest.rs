@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process,
+};
+
+use oxocarbon_utils::parse_hex_rgba_u8 as parse_hex_color;
+
+use crate::compat::{CompatGroup, parse_compat};
+use crate::light;
+use crate::style::scopes;
+
+// variant tags accepted under `[overrides.<tag>]`, in the order the compiler applies them
+const VARIANT_TAGS: [&str; 8] = [
+    "oled",
+    "compat",
+    "light",
+    "monochrome",
+    "focus",
+    "soft",
+    "print",
+    "hc",
+];
+
+/// reads the manifest at `input_src` and every file it includes, depth first: each file's
+/// own content, then its `include` list in order. tables merge, arrays of tables such as
+/// `tokenColors` concatenate, any other key set twice is an error naming both files
+pub(crate) fn load(input_src: &str) -> toml::value::Table {
+    let mut files = Vec::new();
+    collect(Path::new(input_src), &mut Vec::new(), &mut files);
+
+    let mut merged = toml::value::Table::new();
+    let mut origins = HashMap::new();
+    for (path, table) in files {
+        let file = path.display().to_string();
+        if let Err(key) = merge_file(&mut merged, table, "", &file, &mut origins) {
+            eprintln!(
+                "Manifest error ({file}): `{key}` is already set in {}",
+                origins[&key]
+            );
+            process::exit(1);
+        }
+    }
+    merged
+}
+
+fn collect(path: &Path, stack: &mut Vec<PathBuf>, files: &mut Vec<(PathBuf, toml::value::Table)>) {
+    let src = path.display().to_string();
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        eprintln!("Manifest error ({src}): include cycle");
+        process::exit(1);
+    }
+
+    let mut table: toml::value::Table =
+        toml::from_str(&crate::read_input(&src)).unwrap_or_else(|e| {
+            eprintln!("TOML parse error ({src}): {e}");
+            process::exit(1);
+        });
+    let includes: Vec<String> = match table.remove("include") {
+        None => Vec::new(),
+        Some(toml::Value::Array(a)) if a.iter().all(toml::Value::is_str) => a
+            .into_iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        Some(_) => {
+            eprintln!("Manifest error ({src}): `include` must be a list of paths");
+            process::exit(1);
+        }
+    };
+    files.push((path.to_path_buf(), table));
+
+    stack.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for include in includes {
+        collect(&dir.join(include), stack, files);
+    }
+    stack.pop();
+}
+
+// merges one file's table, recording which file set each key. returns the dotted key on a
+// duplicate
+fn merge_file(
+    into: &mut toml::value::Table,
+    from: toml::value::Table,
+    prefix: &str,
+    file: &str,
+    origins: &mut HashMap<String, String>,
+) -> Result<(), String> {
+    for (key, v) in from {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (into.get_mut(&key), v) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(t)) => {
+                merge_file(existing, t, &path, file, origins)?;
+            }
+            (Some(toml::Value::Array(existing)), toml::Value::Array(a))
+                if is_table_array(existing) && is_table_array(&a) =>
+            {
+                existing.extend(a);
+            }
+            (Some(_), _) => return Err(path),
+            (None, v) => {
+                record_origins(&path, &v, file, origins);
+                into.insert(key, v);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn record_origins(path: &str, v: &toml::Value, file: &str, origins: &mut HashMap<String, String>) {
+    origins.insert(path.to_string(), file.to_string());
+    if let toml::Value::Table(t) = v {
+        for (key, v) in t {
+            record_origins(&format!("{path}.{key}"), v, file, origins);
+        }
+    }
+}
+
+#[inline]
+fn is_table_array(a: &[toml::Value]) -> bool {
+    a.iter().all(toml::Value::is_table)
+}
+
+/// manifest-only tables of `oxocarbon.toml`, taken off the theme so they never reach the JSON
+#[derive(Default)]
+pub(crate) struct Manifest {
+    pub(crate) focus: Option<toml::Value>,
+    pub(crate) compat: Vec<CompatGroup>,
+    overrides: toml::value::Table,
+}
+
+impl Manifest {
+    pub(crate) fn take(value: &mut toml::Value, input_src: &str) -> Self {
+        let root = value.as_table_mut().expect("root must be a table");
+        if let Some(palette) = root.remove("palette") {
+            resolve_palette(root, &palette).unwrap_or_else(|e| {
+                eprintln!("Manifest error ({input_src}): {e}");
+                process::exit(1);
+            });
+        }
+        let focus = root.remove("focus");
+        let compat = root
+            .remove("compat")
+            .map(|t| parse_compat(&t))
+            .transpose()
+            .unwrap_or_else(|e| {
+                eprintln!("Manifest error ({input_src}): {e}");
+                process::exit(1);
+            })
+            .unwrap_or_default();
+        let overrides = match root.remove("overrides") {
+            None => toml::value::Table::new(),
+            Some(toml::Value::Table(t)) => t,
+            Some(_) => {
+                eprintln!("Manifest error ({input_src}): `overrides` must be a table");
+                process::exit(1);
+            }
+        };
+        for (tag, v) in &overrides {
+            if !VARIANT_TAGS.contains(&tag.as_str()) || !v.is_table() {
+                eprintln!(
+                    "Manifest error ({input_src}): unknown override table `overrides.{tag}`, expected one of {}",
+                    VARIANT_TAGS.join(", ")
+                );
+                process::exit(1);
+            }
+        }
+        Self {
+            focus,
+            compat,
+            overrides,
+        }
+    }
+
+    /// focus is driven by a manifest table, which a VS Code theme doesn't carry unless an
+    /// overlay adds one. compat falls back to the built-in groups
+    pub(crate) fn check_variants(&self, is_focus: bool, input_src: &str) {
+        if is_focus && self.focus.is_none() {
+            eprintln!("--focus needs a [focus] table, none in {input_src} or its overlays");
+            process::exit(2);
+        }
+    }
+
+    /// applies `[overrides.<tag>]` once the variant's own transforms are done. values are
+    /// authored against the dark palette and go through the light mapping in light variants
+    pub(crate) fn apply_overrides(&self, value: &mut toml::Value, tag: &str, is_light: bool) {
+        let Some(toml::Value::Table(table)) = self.overrides.get(tag) else {
+            return;
+        };
+        let mut table = table.clone();
+        if is_light && tag != "light" {
+            crate::walk_table_strings_mut(&mut table, &mut |s: &mut String| {
+                if let Some(mapped) = light::map_hex(s) {
+                    *s = mapped;
+                }
+            });
+        }
+        merge_theme(value.as_table_mut().expect("root must be a table"), table);
+    }
+}
+
+/// replaces every `"$name"` string with the hex color `name` has in `[palette]`
+fn resolve_palette(root: &mut toml::value::Table, palette: &toml::Value) -> Result<(), String> {
+    let palette = palette.as_table().ok_or("`palette` must be a table")?;
+    for (name, v) in palette {
+        if v.as_str().is_none_or(|s| parse_hex_color(s).is_none()) {
+            return Err(format!("`palette.{name}` must be a hex color"));
+        }
+    }
+    let mut unknown = None;
+    crate::walk_table_strings_mut(root, &mut |s: &mut String| {
+        let Some(name) = s.strip_prefix('$') else {
+            return;
+        };
+        match palette.get(name).and_then(|v| v.as_str()) {
+            Some(hex) => *s = hex.to_ascii_lowercase(),
+            None => {
+                unknown.get_or_insert_with(|| name.to_string());
+            }
+        }
+    });
+    unknown.map_or(Ok(()), |name| {
+        Err(format!("`${name}` isn't defined in [palette]"))
+    })
+}
+
+/// deep-merges a partial theme into `into`: tables merge key by key, `tokenColors` replace
+/// the rule with the same scopes or append, anything else replaces
+pub(crate) fn merge_theme(into: &mut toml::value::Table, partial: toml::value::Table) {
+    for (key, v) in partial {
+        match (into.get_mut(&key), v) {
+            (Some(toml::Value::Array(tokens)), toml::Value::Array(extra))
+                if key == "tokenColors" =>
+            {
+                merge_token_colors(tokens, extra);
+            }
+            (Some(toml::Value::Table(existing)), toml::Value::Table(from)) => {
+                merge_theme(existing, from);
+            }
+            (_, v) => {
+                into.insert(key, v);
+            }
+        }
+    }
+}
+
+fn merge_token_colors(tokens: &mut Vec<toml::Value>, extra: Vec<toml::Value>) {
+    for rule in extra {
+        let key = scope_set(&rule);
+        match tokens.iter_mut().find(|t| scope_set(t) == key) {
+            Some(existing) => *existing = rule,
+            None => tokens.push(rule),
+        }
+    }
+}
+
+// scopes compare as a set, so the order they're listed in doesn't matter
+fn scope_set(rule: &toml::Value) -> Vec<String> {
+    let mut scopes = scopes(rule);
+    scopes.sort_unstable();
+    scopes
+}
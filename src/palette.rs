@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use oxocarbon_utils::{
+    adapt_rgb, format_hex_color, oklch_to_rgb, pack_rgb, parse_hex_rgba_u8 as parse_hex_color,
+    rgb_to_oklch, white_point_adaptation,
+};
+
+// below this oklch chroma a color reads as neutral and is left alone
+pub(crate) const NEUTRAL_CHROMA: f32 = 0.005;
+
+// oxocarbon's accent family, primary first
+pub(crate) const ACCENTS: [&str; 9] = [
+    "#ff7eb6", "#ee5396", "#be95ff", "#78a9ff", "#33b1ff", "#82cfff", "#3ddbd9", "#08bdba",
+    "#42be65",
+];
+
+/// rotates the accent family so the primary lands on `seed`. every other accent keeps its
+/// hue offset from the primary plus its own lightness and chroma, grays are untouched
+pub(crate) fn apply_seed(value: &mut toml::Value, family: &[String], seed: [u8; 3]) {
+    let Some((primary, rest)) = family.split_first() else {
+        return;
+    };
+    let Some((primary_rgb, _)) = parse_hex_color(primary) else {
+        return;
+    };
+    let primary_hue = rgb_to_oklch(primary_rgb)[2];
+    let seed_hue = rgb_to_oklch(seed)[2];
+
+    let mut rotated = HashMap::with_capacity(family.len());
+    rotated.insert(pack_rgb(primary_rgb), seed);
+    for hex in rest {
+        let Some((rgb, _)) = parse_hex_color(hex) else {
+            continue;
+        };
+        let [l, c, h] = rgb_to_oklch(rgb);
+        let hue = (seed_hue + (h - primary_hue)).rem_euclid(360.0);
+        rotated.insert(pack_rgb(rgb), oklch_to_rgb([l, c, hue]));
+    }
+
+    crate::walk_value_strings_mut(value, &mut |s: &mut String| {
+        if let Some((rgb, alpha)) = parse_hex_color(s)
+            && let Some(&to) = rotated.get(&pack_rgb(rgb))
+        {
+            *s = format_hex_color(to, alpha);
+        }
+    });
+}
+
+/// scales oklch chroma of every chromatic color, lightness and hue stay put
+pub(crate) fn apply_chroma(value: &mut toml::Value, factor: f32) {
+    crate::walk_value_strings_mut(value, &mut |s: &mut String| {
+        let Some((rgb, alpha)) = parse_hex_color(s) else {
+            return;
+        };
+        let [l, c, h] = rgb_to_oklch(rgb);
+        if c < NEUTRAL_CHROMA {
+            return;
+        }
+        let scaled = oklch_to_rgb([l, c * factor, h]);
+        if scaled != rgb {
+            *s = format_hex_color(scaled, alpha);
+        }
+    });
+}
+
+/// night shift: bradford-adapts every color from D65 to a blackbody at `kelvin`
+pub(crate) fn apply_white_point(value: &mut toml::Value, kelvin: f32) {
+    let adaptation = white_point_adaptation(kelvin);
+    let mut cache: HashMap<u32, [u8; 3]> = HashMap::new();
+    crate::walk_value_strings_mut(value, &mut |s: &mut String| {
+        let Some((rgb, alpha)) = parse_hex_color(s) else {
+            return;
+        };
+        let adapted = *cache
+            .entry(pack_rgb(rgb))
+            .or_insert_with(|| adapt_rgb(rgb, &adaptation));
+        if adapted != rgb {
+            *s = format_hex_color(adapted, alpha);
+        }
+    });
+}
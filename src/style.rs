@@ -0,0 +1,397 @@
+use oxocarbon_utils::{
+    format_hex_color, oklch_to_rgb, parse_hex_rgba_u8 as parse_hex_color, rgb_to_oklch,
+};
+
+// oklch lightness step standing in for a removed style, bold steps further than italic
+const ITALIC_SHIFT: f32 = 0.06;
+const BOLD_SHIFT: f32 = 0.12;
+// italics `--italic-scopes` moves away are compensated like `--no-italic` ones
+static MOVED_ITALIC: Tone = Tone::Shift(ITALIC_SHIFT);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FontStyle {
+    Italic,
+    Bold,
+}
+
+impl FontStyle {
+    fn keyword(self) -> &'static str {
+        match self {
+            Self::Italic => "italic",
+            Self::Bold => "bold",
+        }
+    }
+}
+
+/// the foreground a styled token rule ends up with
+pub(crate) enum Tone {
+    /// a fixed color, as in the monochrome variants
+    Fixed(String),
+    /// the color the scope inherits, moved in oklch lightness away from the background
+    Shift(f32),
+}
+
+struct StyleRule {
+    style: FontStyle,
+    // only matches rules whose fontStyle is exactly this style
+    exact: bool,
+    tone: Tone,
+}
+
+/// font-style rule set for `tokenColors`, first matching rule wins
+#[derive(Default)]
+pub(crate) struct StylePolicy {
+    rules: Vec<StyleRule>,
+    strip: Vec<FontStyle>,
+    italic_scopes: Option<Vec<String>>,
+}
+
+impl StylePolicy {
+    /// monochrome distinguishes styles by color: italics in `italic_fg`, plain bold in `bold_fg`
+    pub(crate) fn monochrome(&mut self, italic_fg: String, bold_fg: String) {
+        self.rules.push(StyleRule {
+            style: FontStyle::Italic,
+            exact: false,
+            tone: Tone::Fixed(italic_fg),
+        });
+        self.rules.push(StyleRule {
+            style: FontStyle::Bold,
+            exact: true,
+            tone: Tone::Fixed(bold_fg),
+        });
+    }
+
+    /// drops `style` everywhere, compensating with a lightness shift where no earlier rule
+    /// already tells the scope apart
+    pub(crate) fn without(&mut self, style: FontStyle) {
+        self.strip.push(style);
+        self.rules.push(StyleRule {
+            style,
+            exact: false,
+            tone: Tone::Shift(match style {
+                FontStyle::Italic => ITALIC_SHIFT,
+                FontStyle::Bold => BOLD_SHIFT,
+            }),
+        });
+    }
+
+    /// moves italics off their current rules and onto `scopes`. markup.italic keeps its own
+    pub(crate) fn italic_scopes(&mut self, scopes: Vec<String>) {
+        self.italic_scopes = Some(scopes);
+    }
+
+    // the first matching rule's tone, else the moved italic one for rules losing their italic
+    fn tone_for(&self, styles: &[String], moves_italic: bool) -> Option<&Tone> {
+        self.rules
+            .iter()
+            .find(|r| {
+                styles.iter().any(|s| s == r.style.keyword()) && (!r.exact || styles.len() == 1)
+            })
+            .map(|r| &r.tone)
+            .or_else(|| {
+                (moves_italic && styles.iter().any(|s| s == "italic")).then_some(&MOVED_ITALIC)
+            })
+    }
+
+    // whether `scope` loses the italic it has under `italic_scopes`
+    fn moves_italic(&self, scope: &str) -> bool {
+        self.italic_scopes.as_ref().is_some_and(|keep| {
+            !scope_matches(scope, "markup.italic")
+                && !keep.iter().any(|sel| scope_matches(scope, sel))
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.strip.is_empty() && self.italic_scopes.is_none()
+    }
+}
+
+/// rewrites `fontStyle` across `tokenColors` according to `policy`
+pub(crate) fn apply_style_policy(value: &mut toml::Value, policy: &StylePolicy) {
+    let is_light = value
+        .get("type")
+        .and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case("light") || t.eq_ignore_ascii_case("hcLight"));
+    let colors = value.get("colors");
+    let color = |key: &str| colors.and_then(|c| c.get(key)).and_then(|v| v.as_str());
+    let default_fg = color("editor.foreground")
+        .unwrap_or(if is_light { "#161616" } else { "#f2f4f8" })
+        .to_string();
+    // shifts move away from the background, so a removed style never reads dimmer
+    let background_l = color("editor.background")
+        .and_then(parse_hex_color)
+        .map_or(if is_light { 1.0 } else { 0.0 }, |(rgb, _)| {
+            rgb_to_oklch(rgb)[0]
+        });
+    let Some(tokens) = value.get_mut("tokenColors").and_then(|v| v.as_array_mut()) else {
+        return;
+    };
+
+    let inherited = tokens.clone();
+    let items = split_moved_italics(tokens.drain(..), policy);
+    let mut out = Vec::with_capacity(items.len());
+    for mut item in items {
+        let moves_italic = policy.italic_scopes.is_some()
+            && scopes(&item).iter().all(|scope| policy.moves_italic(scope));
+        let Some(styles) = font_styles(&item) else {
+            // a more specific rule with its own color still inherits the style it loses
+            out.extend(shift_inheriting(item, background_l, |scope| {
+                let styles = inherited_font_styles(&inherited, scope)?;
+                match policy.tone_for(&styles, policy.moves_italic(scope))? {
+                    Tone::Shift(delta) => Some(*delta),
+                    Tone::Fixed(_) => None,
+                }
+            }));
+            continue;
+        };
+
+        let tone = policy.tone_for(&styles, moves_italic);
+
+        let kept: Vec<&str> = styles
+            .iter()
+            .map(String::as_str)
+            .filter(|s| {
+                !policy.strip.iter().any(|st| st.keyword() == *s)
+                    && (!moves_italic || *s != "italic")
+            })
+            .collect();
+        if kept.len() != styles.len()
+            && let Some(settings) = item.get_mut("settings").and_then(|v| v.as_table_mut())
+        {
+            if kept.is_empty() {
+                settings.remove("fontStyle");
+                // a style-only rule with its style gone has nothing left to say
+                if settings.is_empty() && tone.is_none() {
+                    continue;
+                }
+            } else {
+                settings.insert("fontStyle".into(), toml::Value::String(kept.join(" ")));
+            }
+        }
+
+        match tone {
+            Some(Tone::Fixed(fg)) => {
+                set_foreground(&mut item, fg);
+                out.push(item);
+            }
+            // the inherited color differs per scope, so the rule is split one scope each
+            Some(Tone::Shift(delta)) if !has_foreground(&item) => {
+                for scope in scopes(&item) {
+                    let base = inherited_foreground(&inherited, &scope).unwrap_or(&default_fg);
+                    let mut split = item.clone();
+                    if let Some(table) = split.as_table_mut() {
+                        table.insert("scope".into(), toml::Value::Array(vec![scope.into()]));
+                    }
+                    set_foreground(&mut split, &shift_lightness(base, *delta, background_l));
+                    out.push(split);
+                }
+            }
+            Some(Tone::Shift(delta)) => {
+                if let Some(fg) =
+                    foreground(&item).map(|fg| shift_lightness(fg, *delta, background_l))
+                {
+                    set_foreground(&mut item, &fg);
+                }
+                out.push(item);
+            }
+            None => out.push(item),
+        }
+    }
+
+    if let Some(scopes) = &policy.italic_scopes {
+        let mut settings = toml::value::Table::new();
+        settings.insert("fontStyle".into(), toml::Value::String("italic".into()));
+        let mut rule = toml::value::Table::new();
+        rule.insert(
+            "scope".into(),
+            toml::Value::Array(scopes.iter().cloned().map(toml::Value::String).collect()),
+        );
+        rule.insert("settings".into(), toml::Value::Table(settings));
+        out.push(toml::Value::Table(rule));
+    }
+
+    *tokens = out;
+}
+
+// shifts the foreground of a rule without its own fontStyle for the scopes `delta` gives a
+// shift, splitting off the scopes that keep their color
+fn shift_inheriting(
+    item: toml::Value,
+    background_l: f32,
+    delta: impl Fn(&str) -> Option<f32>,
+) -> Vec<toml::Value> {
+    let Some(fg) = foreground(&item).map(String::from) else {
+        return vec![item];
+    };
+    // scopes grouped by the shift they need, in first-seen order
+    let mut groups: Vec<(Option<f32>, Vec<String>)> = Vec::new();
+    for scope in scopes(&item) {
+        let d = delta(&scope);
+        match groups.iter_mut().find(|(gd, _)| *gd == d) {
+            Some((_, group)) => group.push(scope),
+            None => groups.push((d, vec![scope])),
+        }
+    }
+    if groups.iter().all(|(d, _)| d.is_none()) {
+        return vec![item];
+    }
+
+    groups
+        .into_iter()
+        .map(|(d, group)| {
+            let mut split = item.clone();
+            if let Some(table) = split.as_table_mut() {
+                table.insert(
+                    "scope".into(),
+                    toml::Value::Array(group.into_iter().map(toml::Value::String).collect()),
+                );
+            }
+            if let Some(d) = d {
+                set_foreground(&mut split, &shift_lightness(&fg, d, background_l));
+            }
+            split
+        })
+        .collect()
+}
+
+// italic rules whose scopes partly keep their italic, split into kept and moved halves
+fn split_moved_italics(
+    tokens: impl Iterator<Item = toml::Value>,
+    policy: &StylePolicy,
+) -> Vec<toml::Value> {
+    tokens
+        .flat_map(|item| match font_styles(&item) {
+            Some(styles) if styles.iter().any(|s| s == "italic") => {
+                split_scopes(item, |scope| policy.moves_italic(scope))
+            }
+            _ => vec![item],
+        })
+        .collect()
+}
+
+// splits a rule into the scopes `pred` holds for and the rest, one rule when it's all one way
+fn split_scopes(item: toml::Value, pred: impl Fn(&str) -> bool) -> Vec<toml::Value> {
+    let (matched, rest): (Vec<String>, Vec<String>) =
+        scopes(&item).into_iter().partition(|scope| pred(scope));
+    if matched.is_empty() || rest.is_empty() {
+        return vec![item];
+    }
+    [matched, rest]
+        .into_iter()
+        .map(|group| {
+            let mut split = item.clone();
+            if let Some(table) = split.as_table_mut() {
+                table.insert(
+                    "scope".into(),
+                    toml::Value::Array(group.into_iter().map(toml::Value::String).collect()),
+                );
+            }
+            split
+        })
+        .collect()
+}
+
+fn font_styles(item: &toml::Value) -> Option<Vec<String>> {
+    let style = item
+        .get("settings")
+        .and_then(|v| v.get("fontStyle"))
+        .and_then(|v| v.as_str())?;
+    Some(style.split_whitespace().map(str::to_lowercase).collect())
+}
+
+fn foreground(item: &toml::Value) -> Option<&str> {
+    item.get("settings")
+        .and_then(|v| v.get("foreground"))
+        .and_then(|v| v.as_str())
+}
+
+#[inline]
+fn has_foreground(item: &toml::Value) -> bool {
+    foreground(item).is_some()
+}
+
+fn set_foreground(item: &mut toml::Value, fg: &str) {
+    if let Some(settings) = item.get_mut("settings").and_then(|v| v.as_table_mut()) {
+        settings.insert("foreground".into(), toml::Value::String(fg.into()));
+    }
+}
+
+// `scope` is an array in oxocarbon.toml, a comma separated string in other themes
+pub(crate) fn scopes(item: &toml::Value) -> Vec<String> {
+    match item.get("scope") {
+        Some(toml::Value::String(s)) => s
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+        Some(toml::Value::Array(arr)) => arr
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// the font styles textmate would give `scope`: the longest matching selector among rules
+// setting fontStyle, later rules winning ties
+fn inherited_font_styles(tokens: &[toml::Value], scope: &str) -> Option<Vec<String>> {
+    tokens
+        .iter()
+        .filter_map(|item| Some((scopes(item), font_styles(item)?)))
+        .filter_map(|(sels, styles)| {
+            sels.iter()
+                .filter(|sel| scope_matches(scope, sel))
+                .map(String::len)
+                .max()
+                .map(|len| (len, styles))
+        })
+        .fold(
+            None,
+            |best: Option<(usize, Vec<String>)>, (len, styles)| match best {
+                Some((best_len, _)) if best_len > len => best,
+                _ => Some((len, styles)),
+            },
+        )
+        .map(|(_, styles)| styles)
+}
+
+// the foreground textmate would pick for `scope`: longest matching selector, later rules
+// winning ties
+fn inherited_foreground<'a>(tokens: &'a [toml::Value], scope: &str) -> Option<&'a str> {
+    tokens
+        .iter()
+        .filter_map(|item| Some((scopes(item), foreground(item)?)))
+        .filter_map(|(sels, fg)| {
+            sels.iter()
+                .filter(|sel| scope_matches(scope, sel))
+                .map(String::len)
+                .max()
+                .map(|len| (len, fg))
+        })
+        .fold(None, |best: Option<(usize, &str)>, (len, fg)| match best {
+            Some((best_len, _)) if best_len > len => best,
+            _ => Some((len, fg)),
+        })
+        .map(|(_, fg)| fg)
+}
+
+/// textmate prefix match: `keyword` selects `keyword.control` but not `keywords`
+#[inline]
+pub(crate) fn scope_matches(scope: &str, selector: &str) -> bool {
+    scope.starts_with(selector)
+        && (scope.len() == selector.len() || scope.as_bytes().get(selector.len()) == Some(&b'.'))
+}
+
+// moves lightness by `delta` away from the background, stopping at the gamut edge rather
+// than stepping back toward it
+fn shift_lightness(hex: &str, delta: f32, background_l: f32) -> String {
+    let Some((rgb, alpha)) = parse_hex_color(hex) else {
+        return hex.to_string();
+    };
+    let [l, c, h] = rgb_to_oklch(rgb);
+    let delta = if l >= background_l { delta } else { -delta };
+    let shifted = oklch_to_rgb([(l + delta).clamp(0.0, 1.0), c, h]);
+    format_hex_color(shifted, alpha)
+}
@@ -0,0 +1,250 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process,
+};
+
+use json_comments::StripComments;
+use oxocarbon_utils::{
+    format_hex_color, oklch_to_rgb, parse_hex_rgba_u8 as parse_hex_color, rgb_to_oklch,
+};
+
+use crate::compat::{CompatGroup, builtin_groups};
+
+// how much more oklch chroma than the editor background a surface may carry and still
+// count as part of the theme's gray ladder
+const LADDER_CHROMA: f32 = 0.02;
+// how far above the editor background a surface may sit and still be a ladder rung
+const LADDER_SPAN: f32 = 0.1;
+// a derived compat gray this close in oklch lightness to one of the theme's own surfaces
+// uses that surface instead
+const LADDER_SNAP: f32 = 0.005;
+// the base the built-in compat groups are written against
+const OXOCARBON_BASE: [u8; 3] = [0x16; 3];
+
+/// reads a VS Code color theme (JSONC allowed) as the compiler's theme table, resolving
+/// `include` the way VS Code does: the included theme's colors are overridden by the
+/// including theme's, its `tokenColors` come first
+pub(crate) fn load(input_src: &str) -> toml::value::Table {
+    let mut theme = load_file(Path::new(input_src), &mut Vec::new());
+    // hex colors compare as strings further down the pipeline
+    crate::walk_table_strings_mut(&mut theme, &mut |s: &mut String| {
+        if parse_hex_color(s).is_some() {
+            s.make_ascii_lowercase();
+        }
+    });
+    theme
+}
+
+fn load_file(path: &Path, stack: &mut Vec<PathBuf>) -> toml::value::Table {
+    let src = path.display().to_string();
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        eprintln!("Theme error ({src}): include cycle");
+        process::exit(1);
+    }
+
+    let mut stripped = String::new();
+    if let Err(e) =
+        StripComments::new(crate::read_input(&src).as_bytes()).read_to_string(&mut stripped)
+    {
+        eprintln!("Failed to read '{src}': {e}");
+        process::exit(1);
+    }
+    let json: serde_json::Value = serde_json::from_str(&strip_trailing_commas(&stripped))
+        .unwrap_or_else(|e| {
+            eprintln!("JSON parse error ({src}): {e}");
+            process::exit(1);
+        });
+    let Some(toml::Value::Table(mut theme)) = json_to_toml(json) else {
+        eprintln!("Theme error ({src}): expected a JSON object");
+        process::exit(1);
+    };
+    if theme.get("tokenColors").is_some_and(toml::Value::is_str) {
+        eprintln!("Theme error ({src}): `tokenColors` as a .tmTheme path isn't supported");
+        process::exit(1);
+    }
+
+    let Some(include) = theme.remove("include") else {
+        return theme;
+    };
+    let Some(include) = include.as_str() else {
+        eprintln!("Theme error ({src}): `include` must be a path");
+        process::exit(1);
+    };
+    stack.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut base = load_file(&dir.join(include), stack);
+    stack.pop();
+
+    for (key, v) in theme {
+        match (base.get_mut(&key), v) {
+            (Some(toml::Value::Array(tokens)), toml::Value::Array(own)) if key == "tokenColors" => {
+                tokens.extend(own);
+            }
+            (Some(toml::Value::Table(into)), toml::Value::Table(own)) => into.extend(own),
+            (_, v) => {
+                base.insert(key, v);
+            }
+        }
+    }
+    base
+}
+
+// toml has no null, so null members are dropped as if they were never set
+fn json_to_toml(json: serde_json::Value) -> Option<toml::Value> {
+    Some(match json {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(b) => toml::Value::Boolean(b),
+        serde_json::Value::Number(n) => n.as_i64().map_or_else(
+            || toml::Value::Float(n.as_f64().unwrap_or_default()),
+            toml::Value::Integer,
+        ),
+        serde_json::Value::String(s) => toml::Value::String(s),
+        serde_json::Value::Array(a) => {
+            toml::Value::Array(a.into_iter().filter_map(json_to_toml).collect())
+        }
+        serde_json::Value::Object(o) => toml::Value::Table(
+            o.into_iter()
+                .filter_map(|(k, v)| Some((k, json_to_toml(v)?)))
+                .collect(),
+        ),
+    })
+}
+
+// jsonc allows a comma before `}` or `]`, json doesn't
+fn strip_trailing_commas(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, ch) in src.char_indices() {
+        if in_string {
+            in_string = ch != '"' || escaped;
+            escaped = ch == '\\' && !escaped;
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == ',' && src[i + 1..].trim_start().starts_with(['}', ']']) {
+            continue;
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// the OLED step for a foreign theme: every neutral surface at or just above the editor
+/// background moves one rung down the theme's own gray ladder, the darkest rung to black
+pub(crate) fn oled_replacements(colors: &toml::value::Table) -> Vec<(String, String)> {
+    let (bg, ladder) = gray_ladder(colors);
+    let bg_l = rgb_to_oklch(bg)[0];
+    let ladder: Vec<_> = ladder
+        .into_iter()
+        .filter(|(l, _)| *l <= bg_l + LADDER_SPAN)
+        .collect();
+
+    // ascending, so a color replaced by one rung is never picked up by the next
+    let mut replacements = Vec::with_capacity(ladder.len());
+    let mut below = [0; 3];
+    for (_, rgb) in ladder {
+        if rgb == [0; 3] {
+            continue;
+        }
+        replacements.push((format_hex_color(rgb, None), format_hex_color(below, None)));
+        below = rgb;
+    }
+    replacements
+}
+
+/// oxocarbon's built-in compat groups moved onto a foreign theme: each gray becomes the color
+/// as far from this theme's editor background in oklch lightness (toward the foreground),
+/// snapped to one of the theme's own neutral surfaces where one is that close. with `oled`
+/// the results then step down the ladder like the surfaces do in [`oled_replacements`], so
+/// `colors` are the ones from before that step
+pub(crate) fn compat_groups(colors: &toml::value::Table, oled: bool) -> Vec<CompatGroup> {
+    let (bg, ladder) = gray_ladder(colors);
+    let [bg_l, bg_c, bg_h] = rgb_to_oklch(bg);
+    let base_l = rgb_to_oklch(OXOCARBON_BASE)[0];
+    let toward_fg = if bg_l > 0.5 { -1.0 } else { 1.0 };
+    // (rung lightness, lightness after the oled step), ascending from black
+    let steps: Vec<(f32, f32)> = oled_replacements(colors)
+        .iter()
+        .filter_map(|(from, to)| {
+            let l = |hex: &str| parse_hex_color(hex).map(|(rgb, _)| rgb_to_oklch(rgb)[0]);
+            Some((l(from)?, l(to)?))
+        })
+        .collect();
+
+    let map = |hex: &str| {
+        let l = parse_hex_color(hex).map_or(base_l, |(rgb, _)| rgb_to_oklch(rgb)[0]);
+        let mut target = (bg_l + toward_fg * (l - base_l)).clamp(0.0, 1.0);
+        if oled {
+            target = step_down(&steps, target);
+        }
+        let rgb = ladder
+            .iter()
+            .map(|&(l, rgb)| ((l - target).abs(), rgb))
+            .filter(|(d, _)| *d <= LADDER_SNAP)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or_else(|| with_lightness(target, bg_c, bg_h), |(_, rgb)| rgb);
+        format_hex_color(rgb, None)
+    };
+    builtin_groups().iter().map(|g| g.remap(&map)).collect()
+}
+
+// the color of chroma `c` and hue `h` that reads back at lightness `l`. searched through
+// `rgb_to_oklch` since `oklch_to_rgb` doesn't round trip dark grays exactly
+fn with_lightness(l: f32, c: f32, h: f32) -> [u8; 3] {
+    let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+    for _ in 0..24 {
+        let mid = 0.5 * (lo + hi);
+        if rgb_to_oklch(oklch_to_rgb([mid, c, h]))[0] < l {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    oklch_to_rgb([hi, c, h])
+}
+
+// where lightness `l` lands after the oled step, interpolating between rungs and keeping
+// the last rung's offset above the ladder
+fn step_down(steps: &[(f32, f32)], l: f32) -> f32 {
+    let mut below = (0.0, 0.0);
+    for &(from, to) in steps {
+        if l <= from {
+            let t = if from > below.0 {
+                (l - below.0) / (from - below.0)
+            } else {
+                1.0
+            };
+            return below.1 + (to - below.1) * t;
+        }
+        below = (from, to);
+    }
+    (l - (below.0 - below.1)).max(0.0)
+}
+
+// the editor background and the theme's neutral surfaces and borders by ascending oklch
+// lightness: its gray ladder
+fn gray_ladder(colors: &toml::value::Table) -> ([u8; 3], Vec<(f32, [u8; 3])>) {
+    let bg = colors
+        .get("editor.background")
+        .and_then(|v| v.as_str())
+        .and_then(parse_hex_color)
+        .map_or([0; 3], |(rgb, _)| rgb);
+    let bg_c = rgb_to_oklch(bg)[1];
+
+    let mut ladder: Vec<(f32, [u8; 3])> = colors
+        .iter()
+        .filter(|(k, _)| {
+            let k = k.to_ascii_lowercase();
+            k.ends_with("background") || k.ends_with("border")
+        })
+        .filter_map(|(_, v)| v.as_str().and_then(parse_hex_color))
+        .map(|(rgb, _)| (rgb_to_oklch(rgb), rgb))
+        .filter(|([_, c, _], _)| *c < bg_c + LADDER_CHROMA)
+        .map(|([l, ..], rgb)| (l, rgb))
+        .collect();
+    ladder.sort_by(|a, b| a.0.total_cmp(&b.0));
+    ladder.dedup_by_key(|(_, rgb)| *rgb);
+    (bg, ladder)
+}